@@ -50,6 +50,9 @@ fi
 echo "-> Build release (as telegrambot)"
 sudo -u telegrambot -H bash -lc "cd ${REPO_DIR} && cargo build --release" || { echo "cargo build failed"; exit 7; }
 
+echo "-> Validate message placeholders"
+sudo -u telegrambot -H bash -lc "cd ${REPO_DIR} && ./target/release/telegram-bot-rust check-messages" || { echo "check-messages failed"; exit 10; }
+
 echo "-> Fix ownership and secure data"
 sudo chown -R telegrambot:telegrambot "${REPO_DIR}"
 if [ -x "./secure-data.sh" ]; then
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fs, path::Path};
+
+/// Which chats the bot serves (OPERATING_MODE, `/modalita`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperatingMode {
+    /// every chat
+    #[default]
+    Public,
+    /// only the chats of ALLOWED_CHATS and `/chat_consentite`
+    Allowlist,
+    /// only private chats
+    PrivateOnly,
+}
+
+impl OperatingMode {
+    /// Name used in the configuration and in the `select` of the messages
+    pub fn name(self) -> &'static str {
+        match self {
+            OperatingMode::Public => "public",
+            OperatingMode::Allowlist => "allowlist",
+            OperatingMode::PrivateOnly => "private_only",
+        }
+    }
+}
+
+/// Parse an operating mode, in English or Italian
+pub fn parse_operating_mode(s: &str) -> Option<OperatingMode> {
+    match s.trim().to_lowercase().as_str() {
+        "public" | "pubblico" => Some(OperatingMode::Public),
+        "allowlist" | "lista" => Some(OperatingMode::Allowlist),
+        "private" | "private_only" | "privato" => Some(OperatingMode::PrivateOnly),
+        _ => None,
+    }
+}
+
+/// Why a chat is not served
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Refusal {
+    /// allowlist mode and the chat is not listed
+    NotAllowed,
+    /// private-only mode and the chat is a group
+    NotPrivate,
+}
+
+/// Runtime changes of the owner, persisted in `data/access.json`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessList {
+    /// set with `/modalita`; `None` follows OPERATING_MODE
+    pub mode: Option<OperatingMode>,
+    /// chats added with `/chat_consentite aggiungi`
+    pub chats: BTreeSet<i64>,
+}
+
+impl AccessList {
+    /// The mode in force, given the configured one
+    pub fn mode(&self, configured: OperatingMode) -> OperatingMode {
+        self.mode.unwrap_or(configured)
+    }
+
+    /// Whether a chat is served; `configured` are the chats of ALLOWED_CHATS
+    pub fn check(
+        &self,
+        configured_mode: OperatingMode,
+        configured: &BTreeSet<i64>,
+        chat_id: i64,
+        is_private: bool,
+    ) -> Result<(), Refusal> {
+        match self.mode(configured_mode) {
+            OperatingMode::Public => Ok(()),
+            OperatingMode::Allowlist
+                if configured.contains(&chat_id) || self.chats.contains(&chat_id) =>
+            {
+                Ok(())
+            }
+            OperatingMode::Allowlist => Err(Refusal::NotAllowed),
+            OperatingMode::PrivateOnly if is_private => Ok(()),
+            OperatingMode::PrivateOnly => Err(Refusal::NotPrivate),
+        }
+    }
+}
+
+/// Parse ALLOWED_CHATS: comma separated chat ids
+pub fn parse_allowed_chats(s: &str) -> Option<BTreeSet<i64>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().ok())
+        .collect()
+}
+
+/// Persisted access list helpers
+pub(crate) fn load_access(path: &Path) -> AccessList {
+    if !path.exists() {
+        return AccessList::default();
+    }
+    match fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str::<AccessList>(&s).unwrap_or_default(),
+        Err(_) => AccessList::default(),
+    }
+}
+
+/// Save the access list to the given path as pretty JSON
+pub(crate) fn save_access(path: &Path, access: &AccessList) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(s) = serde_json::to_string_pretty(access)
+        && fs::write(path, s).is_ok()
+    {
+        crate::status::record_write(path);
+    }
+}
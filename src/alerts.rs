@@ -0,0 +1,92 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// At most this many error notifications reach the owner per hour
+pub const MAX_ALERTS_PER_HOUR: usize = 10;
+
+/// Errors of the last digest period with the same command and error chain
+/// are one notification
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AlertKey {
+    pub command: String,
+    pub error: String,
+}
+
+/// What to do with a handler error
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alert {
+    /// forward it to the owner
+    Notify,
+    /// already forwarded, or over the hourly limit: counted for the digest
+    Suppress,
+}
+
+/// Repeats of one error that were not forwarded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suppressed {
+    pub key: AlertKey,
+    pub count: u64,
+}
+
+/// Deduplication and rate limit of the error notifications sent to the
+/// owner. The digest lists what was suppressed and starts a new period.
+#[derive(Debug, Default)]
+pub struct ErrorAlerts {
+    // errors of the period -> repeats suppressed since
+    period: BTreeMap<AlertKey, u64>,
+    // times of the notifications of the last hour
+    sent: VecDeque<Instant>,
+}
+
+impl ErrorAlerts {
+    /// Decide about an error; the first occurrence of the period is
+    /// forwarded unless the hourly limit is reached
+    pub fn on_error(&mut self, key: AlertKey, now: Instant) -> Alert {
+        while self
+            .sent
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) >= Duration::from_secs(3600))
+        {
+            self.sent.pop_front();
+        }
+        if let Some(repeats) = self.period.get_mut(&key) {
+            *repeats += 1;
+            return Alert::Suppress;
+        }
+        if self.sent.len() >= MAX_ALERTS_PER_HOUR {
+            // not forwarded at all: the first occurrence is a repeat too
+            self.period.insert(key, 1);
+            return Alert::Suppress;
+        }
+        self.sent.push_back(now);
+        self.period.insert(key, 0);
+        Alert::Notify
+    }
+
+    /// The suppressed repeats of the period, most frequent first, and start
+    /// a new period in which every error is forwarded again
+    pub fn take_digest(&mut self) -> Vec<Suppressed> {
+        let mut digest: Vec<Suppressed> = std::mem::take(&mut self.period)
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(key, count)| Suppressed { key, count })
+            .collect();
+        digest.sort_by_key(|s| std::cmp::Reverse(s.count));
+        digest
+    }
+}
+
+/// The command of a message text for the notification: the first word of
+/// a command without the bot mention, `None` for other messages
+pub fn command_of(text: &str) -> Option<String> {
+    let word = text.split_whitespace().next()?;
+    word.starts_with('/')
+        .then(|| word.split('@').next().unwrap_or(word).to_lowercase())
+}
+
+/// Seconds from `now` to the next digest, sent daily at 00:00 UTC
+pub fn secs_until_next_digest(now: u64) -> u64 {
+    86_400 - now % 86_400
+}
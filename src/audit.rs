@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// How a privileged command ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// the change was applied
+    Done,
+    /// allowed, but there was nothing to change
+    Unchanged,
+    /// waiting for a confirmation (e.g. `/reset_starts` before `conferma`)
+    Pending,
+    /// the sender's role was too low
+    Denied,
+    /// allowed, but the change could not be applied
+    Failed,
+}
+
+/// One line of `data/audit_log.jsonl`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// unix timestamp in seconds
+    pub timestamp: u64,
+    pub chat_id: i64,
+    /// `None` for anonymous senders (e.g. anonymous group admins)
+    pub actor: Option<u64>,
+    /// the command without the bot mention, e.g. `/reset_starts`
+    pub command: String,
+    /// the rest of the message text
+    pub args: String,
+    pub outcome: AuditOutcome,
+}
+
+impl AuditEntry {
+    /// Split the text of a command message into command and arguments
+    pub fn new(
+        timestamp: u64,
+        chat_id: i64,
+        actor: Option<u64>,
+        text: &str,
+        outcome: AuditOutcome,
+    ) -> Self {
+        let text = text.trim();
+        let (command, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let command = command.split('@').next().unwrap_or(command).to_lowercase();
+        AuditEntry {
+            timestamp,
+            chat_id,
+            actor,
+            command,
+            args: args.split_whitespace().collect::<Vec<_>>().join(" "),
+            outcome,
+        }
+    }
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC, as shown by `/audit`
+pub fn format_timestamp(ts: u64) -> String {
+    let secs_of_day = ts % (60 * 60 * 24);
+    format!(
+        "{} {:02}:{:02}",
+        crate::seasons::format_date(ts),
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60
+    )
+}
+
+/// Number of pages of `per_page` entries (at least one, even when empty)
+pub fn page_count(len: usize, per_page: usize) -> usize {
+    len.div_ceil(per_page.max(1)).max(1)
+}
+
+/// Entries of a 1-based page, newest first; `None` past the last page
+pub fn audit_page(
+    entries: &[AuditEntry],
+    page: usize,
+    per_page: usize,
+) -> Option<Vec<&AuditEntry>> {
+    if page == 0 || page > page_count(entries.len(), per_page) {
+        return None;
+    }
+    Some(
+        entries
+            .iter()
+            .rev()
+            .skip((page - 1) * per_page)
+            .take(per_page)
+            .collect(),
+    )
+}
+
+/// Load the audit log, one JSON entry per line. Unreadable lines are skipped
+/// so a torn last write cannot hide the rest of the log.
+pub(crate) fn load_audit_log(path: &Path) -> Vec<AuditEntry> {
+    match fs::read_to_string(path) {
+        Ok(s) => s
+            .lines()
+            .filter_map(|l| serde_json::from_str::<AuditEntry>(l).ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Append one entry to the audit log; the file is never rewritten
+pub(crate) fn append_audit_entry(path: &Path, entry: &AuditEntry) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let Ok(line) = serde_json::to_string(entry) else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path)
+        && writeln!(file, "{}", line).is_ok()
+    {
+        crate::status::record_write(path);
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// One ban, global or in one chat
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ban {
+    /// who issued it
+    pub by: u64,
+    /// unix timestamp of the ban
+    pub at: u64,
+    /// unix timestamp of the expiry; `None` for a permanent ban
+    pub until: Option<u64>,
+    pub reason: Option<String>,
+}
+
+impl Ban {
+    /// Whether the ban is still in force at `now`
+    pub fn is_active(&self, now: u64) -> bool {
+        self.until.is_none_or(|u| now < u)
+    }
+}
+
+/// Where a ban applies
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BanScope {
+    /// `/ban ... globale`: every chat
+    Global,
+    /// `/ban ...`: the chat it was issued in
+    Chat(i64),
+}
+
+/// Users whose updates are ignored, persisted in `data/bans.json`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanList {
+    pub global: BTreeMap<u64, Ban>,
+    /// chat id -> banned users of that chat
+    pub chats: BTreeMap<i64, BTreeMap<u64, Ban>>,
+}
+
+impl BanList {
+    /// Whether updates of `user_id` in `chat_id` must be ignored at `now`
+    pub fn is_banned(&self, chat_id: i64, user_id: u64, now: u64) -> bool {
+        self.global.get(&user_id).is_some_and(|b| b.is_active(now))
+            || self
+                .chats
+                .get(&chat_id)
+                .and_then(|c| c.get(&user_id))
+                .is_some_and(|b| b.is_active(now))
+    }
+
+    /// Ban a user, replacing an earlier ban in the same scope
+    pub fn ban(&mut self, scope: BanScope, user_id: u64, ban: Ban) {
+        match scope {
+            BanScope::Global => {
+                self.global.insert(user_id, ban);
+            }
+            BanScope::Chat(chat_id) => {
+                self.chats.entry(chat_id).or_default().insert(user_id, ban);
+            }
+        }
+    }
+
+    /// Lift a ban; `false` if the user was not banned in that scope
+    pub fn unban(&mut self, scope: BanScope, user_id: u64) -> bool {
+        match scope {
+            BanScope::Global => self.global.remove(&user_id).is_some(),
+            BanScope::Chat(chat_id) => {
+                let Some(bans) = self.chats.get_mut(&chat_id) else {
+                    return false;
+                };
+                let removed = bans.remove(&user_id).is_some();
+                if bans.is_empty() {
+                    self.chats.remove(&chat_id);
+                }
+                removed
+            }
+        }
+    }
+
+    /// Active bans of a scope, by user id
+    pub fn active(&self, scope: BanScope, now: u64) -> Vec<(u64, &Ban)> {
+        let bans = match scope {
+            BanScope::Global => Some(&self.global),
+            BanScope::Chat(chat_id) => self.chats.get(&chat_id),
+        };
+        bans.into_iter()
+            .flatten()
+            .filter(|(_, b)| b.is_active(now))
+            .map(|(u, b)| (*u, b))
+            .collect()
+    }
+
+    /// Drop expired bans; returns how many were removed
+    pub fn remove_expired(&mut self, now: u64) -> usize {
+        let before = self.len();
+        self.global.retain(|_, b| b.is_active(now));
+        for bans in self.chats.values_mut() {
+            bans.retain(|_, b| b.is_active(now));
+        }
+        self.chats.retain(|_, bans| !bans.is_empty());
+        before - self.len()
+    }
+
+    fn len(&self) -> usize {
+        self.global.len() + self.chats.values().map(BTreeMap::len).sum::<usize>()
+    }
+}
+
+/// Parse a ban duration such as `30m`, `12h`, `7d` or `2w` into seconds
+pub fn parse_ban_duration(s: &str) -> Option<u64> {
+    let s = s.trim().to_lowercase();
+    let unit = s.chars().last()?;
+    let secs = match unit {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 60 * 60 * 24,
+        'w' => 60 * 60 * 24 * 7,
+        _ => return None,
+    };
+    let n: u64 = s[..s.len() - 1].parse().ok()?;
+    if n == 0 {
+        return None;
+    }
+    n.checked_mul(secs)
+}
+
+/// Persisted ban list helpers
+pub(crate) fn load_bans(path: &Path) -> BanList {
+    if !path.exists() {
+        return BanList::default();
+    }
+    match fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str::<BanList>(&s).unwrap_or_default(),
+        Err(_) => BanList::default(),
+    }
+}
+
+/// Save the ban list to the given path as pretty JSON
+pub(crate) fn save_bans(path: &Path, bans: &BanList) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(s) = serde_json::to_string_pretty(bans)
+        && fs::write(path, s).is_ok()
+    {
+        crate::status::record_write(path);
+    }
+}
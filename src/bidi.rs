@@ -0,0 +1,74 @@
+/// FIRST STRONG ISOLATE: the isolated text takes the direction of its first
+/// strong character, so both "Ahmed" and "أحمد" render correctly
+pub const FSI: char = '\u{2068}';
+/// LEFT-TO-RIGHT ISOLATE, used for command names
+pub const LRI: char = '\u{2066}';
+/// POP DIRECTIONAL ISOLATE, closes an FSI or LRI
+pub const PDI: char = '\u{2069}';
+
+/// Whether `tag` is written right to left. An explicit script subtag wins
+/// (`pa-Arab` is RTL, `az-Latn` is not); otherwise the primary language
+/// decides.
+pub fn is_rtl(tag: &str) -> bool {
+    let mut subtags = tag.split(['-', '_']);
+    let primary = subtags.next().unwrap_or("").to_ascii_lowercase();
+    if let Some(script) = subtags.next().filter(|s| s.len() == 4) {
+        return matches!(
+            script.to_ascii_lowercase().as_str(),
+            "arab" | "hebr" | "syrc" | "thaa" | "nkoo" | "adlm" | "rohg"
+        );
+    }
+    matches!(
+        primary.as_str(),
+        "ar" | "he" | "iw" | "fa" | "ur" | "ps" | "sd" | "ug" | "yi" | "dv" | "ckb"
+    )
+}
+
+/// Wrap a substituted value in FSI … PDI so it cannot reorder the text
+/// around it. Isolates end at a line break, so each line is wrapped on its
+/// own; empty lines are left alone.
+pub fn isolate(value: &str) -> String {
+    value
+        .split('\n')
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}{}", FSI, line, PDI)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Wrap every command name (`/gioco`, `/cancella_dati`, ...) of an RTL
+/// template in LRI … PDI, so the slash stays in front of the name. A slash
+/// preceded by a letter, a digit, `/` or `:` (as in URLs) is not a command,
+/// and one that is already isolated is left as is.
+pub fn isolate_commands(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev: Option<char> = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let starts_command = c == '/'
+            && !prev.is_some_and(|p| p.is_alphanumeric() || p == '/' || p == ':' || p == LRI)
+            && chars.peek().is_some_and(|n| n.is_ascii_alphabetic());
+        if !starts_command {
+            out.push(c);
+            prev = Some(c);
+            continue;
+        }
+        out.push(LRI);
+        out.push('/');
+        while let Some(&n) = chars.peek() {
+            if !(n.is_ascii_alphanumeric() || n == '_') {
+                break;
+            }
+            out.push(n);
+            chars.next();
+        }
+        out.push(PDI);
+        prev = Some(PDI);
+    }
+    out
+}
@@ -0,0 +1,106 @@
+use crate::{AppState, Lang, lookup_lang, parse_lang};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+/// The text of `/annuncio`, possibly translated: lines starting with a
+/// `[tag]` marker begin the variant for that language; text before the
+/// first marker is the variant for everybody else.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Announcement {
+    variants: Vec<(Option<Lang>, String)>,
+}
+
+impl Announcement {
+    /// Parse the arguments of `/annuncio`; `None` if there is no text or a
+    /// marker is not a language tag
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut variants: Vec<(Option<Lang>, String)> = vec![(None, String::new())];
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            let marker = trimmed
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'));
+            match marker {
+                Some((tag, rest)) => {
+                    variants.push((Some(parse_lang(tag)?), rest.trim_start().to_string()));
+                }
+                None => {
+                    let (_, current) = variants.last_mut()?;
+                    if !current.is_empty() {
+                        current.push('\n');
+                    }
+                    current.push_str(line);
+                }
+            }
+        }
+        let variants: Vec<_> = variants
+            .into_iter()
+            .map(|(lang, text)| (lang, text.trim().to_string()))
+            .filter(|(_, text)| !text.is_empty())
+            .collect();
+        (!variants.is_empty()).then_some(Announcement { variants })
+    }
+
+    /// The variant for a chat speaking `lang`, along its lookup chain; the
+    /// untranslated text (or the first variant) otherwise
+    pub fn text_for(&self, lang: &Lang, aliases: &HashMap<String, Lang>) -> &str {
+        let tagged = |t: &str| {
+            self.variants
+                .iter()
+                .any(|(l, _)| l.as_ref().is_some_and(|l| l.tag() == t))
+        };
+        let found = lookup_lang(lang, aliases, tagged).and_then(|found| {
+            self.variants
+                .iter()
+                .find(|(l, _)| l.as_ref() == Some(&found))
+        });
+        found
+            .or_else(|| self.variants.iter().find(|(l, _)| l.is_none()))
+            .unwrap_or(&self.variants[0])
+            .1
+            .as_str()
+    }
+}
+
+/// Every chat the bot has stored something about: chat settings, games,
+/// progression, statistics, season scores and moderators
+pub fn known_chats(state: &AppState) -> BTreeSet<i64> {
+    let from_key = |k: &String| k.rsplit_once(':').and_then(|(c, _)| c.parse::<i64>().ok());
+    let mut chats: BTreeSet<i64> = state.chat_settings.keys().copied().collect();
+    chats.extend(state.by_user.keys().map(|(c, _)| *c));
+    chats.extend(state.user_langs.keys().map(|(c, _)| *c));
+    chats.extend(state.seen_welcome.keys().filter_map(from_key));
+    chats.extend(state.user_start_attempts.keys().filter_map(from_key));
+    chats.extend(state.user_stats.keys().filter_map(from_key));
+    chats.extend(state.season.scores.keys().filter_map(from_key));
+    chats.extend(state.role_grants.moderators.keys().copied());
+    chats
+}
+
+/// Persisted maintenance mode: the unix timestamp it was turned on, or
+/// `None` (no `data/maintenance.json`) when it is off
+pub(crate) fn load_maintenance(path: &Path) -> Option<u64> {
+    let s = fs::read_to_string(path).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&s).ok()?;
+    value.get("since")?.as_u64()
+}
+
+/// Save the maintenance mode, removing the file when it is turned off
+pub(crate) fn save_maintenance(path: &Path, since: Option<u64>) {
+    match since {
+        Some(ts) => {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if fs::write(path, serde_json::json!({ "since": ts }).to_string()).is_ok() {
+                crate::status::record_write(path);
+            }
+        }
+        None => {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{Lang, MessageCatalog, Messages, default_messages, load_message_catalog, validation};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
+
+/// The message catalogs in use, one `Messages` per language tag. It lives in
+/// the otherwise immutable `Config`, so `/reload_messages` swaps the whole map
+/// at once; readers take a snapshot and never see a half-reloaded catalog.
+#[derive(Debug, Default)]
+pub struct MessageStore(RwLock<Arc<HashMap<String, Messages>>>);
+
+impl MessageStore {
+    pub fn new(messages: HashMap<String, Messages>) -> Self {
+        MessageStore(RwLock::new(Arc::new(messages)))
+    }
+
+    /// The catalogs as they are now
+    pub fn snapshot(&self) -> Arc<HashMap<String, Messages>> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// The messages of one language tag
+    pub fn get(&self, tag: &str) -> Option<Messages> {
+        self.snapshot().get(tag).cloned()
+    }
+
+    /// Replace every catalog
+    pub fn replace(&self, messages: HashMap<String, Messages>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(messages);
+    }
+}
+
+/// A clone starts from the current catalogs; later reloads of either copy
+/// don't affect the other.
+impl Clone for MessageStore {
+    fn clone(&self) -> Self {
+        MessageStore(RwLock::new(self.snapshot()))
+    }
+}
+
+impl From<HashMap<String, Messages>> for MessageStore {
+    fn from(messages: HashMap<String, Messages>) -> Self {
+        MessageStore::new(messages)
+    }
+}
+
+/// What a successful reload swapped in
+#[derive(Debug)]
+pub struct ReloadOutcome {
+    /// loaded language tags, sorted
+    pub languages: Vec<String>,
+    /// language tag -> keys its file lacks, filled from the fallbacks
+    pub missing_keys: BTreeMap<String, Vec<String>>,
+}
+
+/// Load the message files of `dir` and check them the way a reload requires:
+/// every file must parse, `default_lang` must have a file and every
+/// placeholder must match the English reference. English is always present,
+/// from the built-ins if `en.json` is missing.
+pub fn load_checked_catalog(dir: &str, default_lang: &Lang) -> Result<MessageCatalog, Vec<String>> {
+    let mut catalog = load_message_catalog(dir, default_lang);
+    let mut problems: Vec<String> = catalog.unreadable.values().cloned().collect();
+    if catalog.messages.is_empty() {
+        problems.push(format!("no message files found in {}", dir));
+    }
+    catalog
+        .messages
+        .entry("en".to_string())
+        .or_insert_with(default_messages);
+    if !catalog.messages.contains_key(default_lang.tag())
+        && !catalog.messages.contains_key(default_lang.primary())
+    {
+        problems.push(format!(
+            "no {}/{}.json for the default language",
+            dir, default_lang
+        ));
+    }
+    problems.extend(
+        validation::validate_placeholders(&catalog.messages)
+            .iter()
+            .map(ToString::to_string),
+    );
+    if problems.is_empty() {
+        Ok(catalog)
+    } else {
+        Err(problems)
+    }
+}
+
+/// Reload the message files of `dir` into `store`. On any problem the
+/// catalogs in use are kept and the problems are returned.
+pub fn reload_messages(
+    store: &MessageStore,
+    dir: &str,
+    default_lang: &Lang,
+) -> Result<ReloadOutcome, Vec<String>> {
+    let catalog = load_checked_catalog(dir, default_lang)?;
+    let mut languages: Vec<String> = catalog.messages.keys().cloned().collect();
+    languages.sort_unstable();
+    store.replace(catalog.messages);
+    Ok(ReloadOutcome {
+        languages,
+        missing_keys: catalog.missing_keys,
+    })
+}
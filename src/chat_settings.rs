@@ -0,0 +1,182 @@
+use crate::Lang;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::Path,
+};
+
+/// Group-level preferences, changed only by chat administrators and
+/// persisted in `data/chat_settings.json`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatSettings {
+    /// language for members without a personal `/lang` preference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Lang>,
+    /// game rules set with `/imposta`, replacing the `Config` values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_threshold: Option<i32>,
+}
+
+impl ChatSettings {
+    /// `defaults` with the overrides of this chat applied
+    pub fn game_rules(&self, defaults: GameRules) -> GameRules {
+        GameRules {
+            min: self.min.unwrap_or(defaults.min),
+            max: self.max.unwrap_or(defaults.max),
+            attempts: self.attempts.unwrap_or(defaults.attempts),
+            restart_threshold: self.restart_threshold.unwrap_or(defaults.restart_threshold),
+        }
+    }
+
+    /// Whether the chat overrides any game rule
+    pub fn has_game_overrides(&self) -> bool {
+        self.min.is_some()
+            || self.max.is_some()
+            || self.attempts.is_some()
+            || self.restart_threshold.is_some()
+    }
+
+    /// Drop every game rule override, keeping the other settings
+    pub fn clear_game_overrides(&mut self) {
+        self.min = None;
+        self.max = None;
+        self.attempts = None;
+        self.restart_threshold = None;
+    }
+
+    /// Whether nothing is set and the chat can be forgotten
+    pub fn is_empty(&self) -> bool {
+        self.lang.is_none() && !self.has_game_overrides()
+    }
+}
+
+/// The number range and attempts of the games of a chat: the `Config`
+/// values, possibly overridden with `/imposta`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameRules {
+    pub min: i32,
+    pub max: i32,
+    pub attempts: i32,
+    pub restart_threshold: i32,
+}
+
+/// Why a set of game rules cannot be used
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RulesError {
+    MinNotBelowMax { min: i32, max: i32 },
+    AttemptsNotPositive(i32),
+    NegativeThreshold(i32),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::MinNotBelowMax { min, max } => write!(
+                f,
+                "GAME_MIN ({}) must be less than GAME_MAX ({}).",
+                min, max
+            ),
+            RulesError::AttemptsNotPositive(a) => {
+                write!(f, "GAME_ATTEMPTS ({}) must be a positive integer.", a)
+            }
+            RulesError::NegativeThreshold(t) => {
+                write!(f, "NUMBER_ATTEMPTS ({}) must be a non-negative integer.", t)
+            }
+        }
+    }
+}
+
+impl GameRules {
+    /// The checks `run_bot` applies to the environment at startup
+    pub fn validate(&self) -> Result<(), RulesError> {
+        if self.min >= self.max {
+            return Err(RulesError::MinNotBelowMax {
+                min: self.min,
+                max: self.max,
+            });
+        }
+        if self.attempts <= 0 {
+            return Err(RulesError::AttemptsNotPositive(self.attempts));
+        }
+        if self.restart_threshold < 0 {
+            return Err(RulesError::NegativeThreshold(self.restart_threshold));
+        }
+        Ok(())
+    }
+}
+
+/// Apply the `key value` pairs of `/imposta` (`min`, `max`, `tentativi`,
+/// `soglia`) to `settings`. The result is not validated; `None` if the
+/// arguments are malformed.
+pub fn apply_rule_args(settings: &ChatSettings, args: &[&str]) -> Option<ChatSettings> {
+    if args.is_empty() || !args.len().is_multiple_of(2) {
+        return None;
+    }
+    let mut out = settings.clone();
+    for pair in args.chunks(2) {
+        let value: i32 = pair[1].parse().ok()?;
+        let slot = match pair[0].to_lowercase().as_str() {
+            "min" => &mut out.min,
+            "max" => &mut out.max,
+            "tentativi" | "attempts" => &mut out.attempts,
+            "soglia" | "threshold" => &mut out.restart_threshold,
+            _ => return None,
+        };
+        *slot = Some(value);
+    }
+    Some(out)
+}
+
+/// How long the administrators of a chat are trusted before asking Telegram again
+pub const CHAT_ADMINS_TTL_SECS: u64 = 10 * 60;
+
+/// Administrators fetched with `getChatAdministrators`, per chat, with the
+/// time they were fetched.
+#[derive(Debug, Default)]
+pub struct AdminCache {
+    entries: HashMap<i64, (u64, HashSet<u64>)>,
+}
+
+impl AdminCache {
+    /// Cached administrators of `chat_id`, unless older than the TTL
+    pub fn get(&self, chat_id: i64, now: u64) -> Option<&HashSet<u64>> {
+        self.entries
+            .get(&chat_id)
+            .filter(|(fetched, _)| now.saturating_sub(*fetched) < CHAT_ADMINS_TTL_SECS)
+            .map(|(_, admins)| admins)
+    }
+
+    pub fn insert(&mut self, chat_id: i64, admins: HashSet<u64>, now: u64) {
+        self.entries.insert(chat_id, (now, admins));
+    }
+}
+
+/// Persisted chat settings helpers
+pub(crate) fn load_chat_settings(path: &Path) -> HashMap<i64, ChatSettings> {
+    if !path.exists() {
+        return HashMap::new();
+    }
+    match fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str::<HashMap<i64, ChatSettings>>(&s).unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Save the chat settings map to the given path as pretty JSON
+pub(crate) fn save_chat_settings(path: &Path, map: &HashMap<i64, ChatSettings>) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(s) = serde_json::to_string_pretty(map)
+        && fs::write(path, s).is_ok()
+    {
+        crate::status::record_write(path);
+    }
+}
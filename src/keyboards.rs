@@ -0,0 +1,138 @@
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+/// Callback data prefixes. Telegram limits callback data to 64 bytes, so the
+/// payload only carries ids; everything else is looked up in the state.
+const CB_PLAY_AGAIN: &str = "again";
+const CB_LANG: &str = "lang";
+const CB_KEYPAD: &str = "kp";
+
+/// Longest guess that can be composed on the keypad (sign included)
+pub const KEYPAD_MAX_LEN: usize = 10;
+
+/// A key of the numeric keypad
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeypadKey {
+    Digit(u8),
+    Sign,
+    Backspace,
+    Submit,
+}
+
+/// Action requested by an inline keyboard button
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallbackAction {
+    /// restart the game of `user_id` after the game `game_id` ended
+    PlayAgain { user_id: u64, game_id: u64 },
+    /// set the presser's language
+    SetLang { tag: String },
+    /// keypad key for the game `game_id` of `user_id`
+    Keypad {
+        user_id: u64,
+        game_id: u64,
+        key: KeypadKey,
+    },
+}
+
+/// Parse callback data produced by the keyboards below. Unknown or malformed
+/// data (e.g. from an older bot version) yields `None`.
+pub fn parse_callback_data(data: &str) -> Option<CallbackAction> {
+    let parts: Vec<&str> = data.split(':').collect();
+    match parts.as_slice() {
+        [CB_PLAY_AGAIN, user, game] => Some(CallbackAction::PlayAgain {
+            user_id: user.parse().ok()?,
+            game_id: game.parse().ok()?,
+        }),
+        [CB_LANG, tag] if !tag.is_empty() => Some(CallbackAction::SetLang {
+            tag: tag.to_string(),
+        }),
+        [CB_KEYPAD, user, game, key] => {
+            let key = match *key {
+                "sign" => KeypadKey::Sign,
+                "del" => KeypadKey::Backspace,
+                "ok" => KeypadKey::Submit,
+                d if d.len() == 1 => KeypadKey::Digit(d.parse().ok()?),
+                _ => return None,
+            };
+            Some(CallbackAction::Keypad {
+                user_id: user.parse().ok()?,
+                game_id: game.parse().ok()?,
+                key,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Apply a keypad key (other than submit) to the input composed so far
+pub fn apply_keypad_key(input: &str, key: KeypadKey) -> String {
+    let mut out = input.to_string();
+    match key {
+        KeypadKey::Digit(d) => {
+            if out.len() < KEYPAD_MAX_LEN {
+                out.push(char::from(b'0' + d));
+            }
+        }
+        KeypadKey::Sign => {
+            if let Some(rest) = out.strip_prefix('-') {
+                out = rest.to_string();
+            } else if out.len() < KEYPAD_MAX_LEN {
+                out.insert(0, '-');
+            }
+        }
+        KeypadKey::Backspace => {
+            out.pop();
+        }
+        KeypadKey::Submit => {}
+    }
+    out
+}
+
+/// "Play again" button attached to the end-of-game messages
+pub fn play_again_keyboard(label: &str, user_id: u64, game_id: u64) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        label.to_string(),
+        format!("{}:{}:{}", CB_PLAY_AGAIN, user_id, game_id),
+    )]])
+}
+
+/// Language picker for `/lang`: one button per loaded language, three per row.
+/// `languages` holds `(tag, button label)` pairs.
+pub fn language_keyboard(languages: &[(String, String)]) -> InlineKeyboardMarkup {
+    let rows = languages
+        .chunks(3)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|(tag, label)| {
+                    InlineKeyboardButton::callback(label.clone(), format!("{}:{}", CB_LANG, tag))
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Numeric keypad bound to one game of one user
+pub fn keypad_keyboard(user_id: u64, game_id: u64) -> InlineKeyboardMarkup {
+    let button = |label: &str, key: &str| {
+        InlineKeyboardButton::callback(
+            label.to_string(),
+            format!("{}:{}:{}:{}", CB_KEYPAD, user_id, game_id, key),
+        )
+    };
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = ["123", "456", "789"]
+        .iter()
+        .map(|row| {
+            row.chars()
+                .map(|c| button(&c.to_string(), &c.to_string()))
+                .collect()
+        })
+        .collect();
+    rows.push(vec![
+        button("±", "sign"),
+        button("0", "0"),
+        button("⌫", "del"),
+    ]);
+    rows.push(vec![button("✅", "ok")]);
+    InlineKeyboardMarkup::new(rows)
+}
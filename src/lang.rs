@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// A language, identified by a normalized BCP-47 tag such as `en`, `it` or
+/// `pt-BR`. The set of languages the bot speaks is not fixed: it is whatever
+/// `messages/<tag>.json` files are found at startup.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Lang(String);
+
+impl Lang {
+    /// English, the language of the built-in messages
+    pub fn en() -> Self {
+        Lang("en".to_string())
+    }
+
+    /// The normalized tag (e.g. "en", "zh-Hant", "pt-BR")
+    pub fn tag(&self) -> &str {
+        &self.0
+    }
+
+    /// The primary language subtag (e.g. "pt" for "pt-BR")
+    pub fn primary(&self) -> &str {
+        self.0.split('-').next().unwrap_or(&self.0)
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Parse and normalize a BCP-47 language tag (`_` is accepted as separator,
+/// as in `en_US`). Returns `None` for anything that is not a well-formed tag.
+///
+/// Supported shape: language (2-3 or 5-8 letters), optional script (4
+/// letters), optional region (2 letters or 3 digits), variants, extensions
+/// and a private-use part. Case is normalized as recommended: `zh-Hant-TW`.
+pub fn parse_lang(s: &str) -> Option<Lang> {
+    let s = s.trim();
+    if s.is_empty() || s.len() > 64 {
+        return None;
+    }
+    let subtags: Vec<&str> = s.split(['-', '_']).collect();
+    let mut out: Vec<String> = Vec::with_capacity(subtags.len());
+    let mut iter = subtags.into_iter().peekable();
+
+    let language = iter.next()?;
+    let alpha = |t: &str| t.chars().all(|c| c.is_ascii_alphabetic());
+    let alnum = |t: &str| t.chars().all(|c| c.is_ascii_alphanumeric());
+    if !(alpha(language) && matches!(language.len(), 2..=3 | 5..=8)) {
+        return None;
+    }
+    out.push(language.to_ascii_lowercase());
+
+    if let Some(script) = iter.next_if(|t| t.len() == 4 && alpha(t)) {
+        let mut chars = script.chars();
+        let first = chars.next()?.to_ascii_uppercase();
+        out.push(
+            std::iter::once(first)
+                .chain(chars.map(|c| c.to_ascii_lowercase()))
+                .collect(),
+        );
+    }
+    if let Some(region) = iter.next_if(|t| {
+        (t.len() == 2 && alpha(t)) || (t.len() == 3 && t.chars().all(|c| c.is_ascii_digit()))
+    }) {
+        out.push(region.to_ascii_uppercase());
+    }
+    while let Some(variant) = iter.next_if(|t| {
+        alnum(t)
+            && ((5..=8).contains(&t.len())
+                || (t.len() == 4 && t.starts_with(|c: char| c.is_ascii_digit())))
+    }) {
+        out.push(variant.to_ascii_lowercase());
+    }
+    // extensions (`u-...`) and private use (`x-...`): a singleton followed by
+    // at least one subtag
+    while let Some(singleton) = iter.next() {
+        if singleton.len() != 1 || !alnum(singleton) {
+            return None;
+        }
+        let private = singleton.eq_ignore_ascii_case("x");
+        let min_len = if private { 1 } else { 2 };
+        let mut count = 0;
+        out.push(singleton.to_ascii_lowercase());
+        while let Some(part) = iter.next_if(|t| alnum(t) && (min_len..=8).contains(&t.len())) {
+            out.push(part.to_ascii_lowercase());
+            count += 1;
+        }
+        if count == 0 {
+            return None;
+        }
+    }
+    Some(Lang(out.join("-")))
+}
+
+impl serde::Serialize for Lang {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Lang {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_lang(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid language tag: {}", s)))
+    }
+}
+
+/// Lookup chain of a tag, most specific first, as in RFC 4647 lookup:
+/// `zh-Hant-TW` → `zh-Hant-TW`, `zh-Hant`, `zh`. An extension or private-use
+/// singleton is dropped together with its subtags (`en-US-x-twain` → `en-US`).
+pub fn fallback_chain(lang: &Lang) -> Vec<Lang> {
+    let mut subtags: Vec<&str> = lang.tag().split('-').collect();
+    let mut chain = Vec::with_capacity(subtags.len());
+    while !subtags.is_empty() {
+        chain.push(Lang(subtags.join("-")));
+        subtags.pop();
+        if subtags.last().is_some_and(|t| t.len() == 1) {
+            subtags.pop();
+        }
+    }
+    chain
+}
+
+/// Aliases applied when no messages file matches a tag: deprecated codes and
+/// regional Chinese tags that mean a script. `LANG_ALIASES` can add to or
+/// override them.
+pub fn default_lang_aliases() -> HashMap<String, Lang> {
+    [
+        ("iw", "he"),
+        ("in", "id"),
+        ("ji", "yi"),
+        ("no", "nb"),
+        ("zh-TW", "zh-Hant"),
+        ("zh-HK", "zh-Hant"),
+        ("zh-MO", "zh-Hant"),
+        ("zh-CN", "zh-Hans"),
+        ("zh-SG", "zh-Hans"),
+    ]
+    .into_iter()
+    .map(|(from, to)| (from.to_string(), Lang(to.to_string())))
+    .collect()
+}
+
+/// Parse a `LANG_ALIASES` value such as `zh-TW=zh-Hant, pt-PT=pt`. Both sides
+/// must be valid BCP-47 tags.
+pub fn parse_lang_aliases(s: &str) -> Result<HashMap<String, Lang>, String> {
+    let mut aliases = HashMap::new();
+    for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (from, to) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("{} is not written as <tag>=<tag>", entry))?;
+        let from = parse_lang(from).ok_or_else(|| format!("{} is not a valid tag", from.trim()))?;
+        let to = parse_lang(to).ok_or_else(|| format!("{} is not a valid tag", to.trim()))?;
+        aliases.insert(from.0, to);
+    }
+    Ok(aliases)
+}
+
+/// The first language `available` accepts along the lookup chain of `lang`.
+/// At each step the tag itself is tried before its alias (and the alias'
+/// own chain), so a dedicated `zh-TW.json` wins over the `zh-Hant` alias.
+pub fn lookup_lang(
+    lang: &Lang,
+    aliases: &HashMap<String, Lang>,
+    available: impl Fn(&str) -> bool,
+) -> Option<Lang> {
+    for step in fallback_chain(lang) {
+        if available(step.tag()) {
+            return Some(step);
+        }
+        if let Some(alias) = aliases.get(step.tag())
+            && let Some(found) = fallback_chain(alias)
+                .into_iter()
+                .find(|l| available(l.tag()))
+        {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Everything that may choose the language of a reply, in priority order
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LangPreferences<'a> {
+    /// set by the user with `/lang <tag>` in this chat
+    pub user: Option<&'a Lang>,
+    /// set by a chat administrator with `/lang chat <tag>`
+    pub chat: Option<&'a Lang>,
+    /// the `language_code` Telegram reports for the user
+    pub language_code: Option<&'a str>,
+}
+
+/// Pick the language of a reply: the user override, then the chat override,
+/// then Telegram's `language_code`, each resolved with [`lookup_lang`]; the
+/// first one `available` accepts wins, otherwise `default`.
+pub fn negotiate_lang(
+    prefs: LangPreferences<'_>,
+    aliases: &HashMap<String, Lang>,
+    default: &Lang,
+    available: impl Fn(&str) -> bool,
+) -> Lang {
+    let code = prefs.language_code.and_then(parse_lang);
+    [prefs.user, prefs.chat, code.as_ref()]
+        .into_iter()
+        .flatten()
+        .find_map(|lang| lookup_lang(lang, aliases, &available))
+        .unwrap_or_else(|| default.clone())
+}
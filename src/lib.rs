@@ -1,40 +1,153 @@
 use anyhow::Result;
 use dotenvy::dotenv;
 use rand::{Rng, distributions::Uniform};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
-    env, fs,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
     path::Path,
     sync::Arc,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use teloxide::{
+    prelude::*,
+    types::{InputFile, User},
 };
-use teloxide::prelude::*;
 use tokio::sync::RwLock;
 
+pub mod access;
+pub mod alerts;
+pub mod audit;
+pub mod bans;
+pub mod bidi;
+pub mod broadcast;
+pub mod catalog;
+pub mod chat_settings;
+pub mod keyboards;
+pub mod lang;
+pub mod numbers;
+pub mod plural;
+pub mod privacy;
+pub mod ratelimit;
+pub mod rating;
+pub mod resets;
+pub mod roles;
+pub mod seasons;
+pub mod status;
+pub mod validation;
+
+use access::{AccessList, OperatingMode, Refusal};
+use alerts::{Alert, AlertKey, ErrorAlerts};
+use audit::{AuditEntry, AuditOutcome};
+use bans::{Ban, BanList, BanScope};
+use broadcast::Announcement;
+use catalog::MessageStore;
+use chat_settings::{AdminCache, ChatSettings, GameRules, RulesError};
+use keyboards::{CallbackAction, KeypadKey};
+pub use lang::{Lang, LangPreferences, lookup_lang, negotiate_lang, parse_lang};
+use ratelimit::{Limited, RateLimit, RateLimiter, RateLimits, Verdict};
+use rating::{GameRating, Guess, UserStats};
+use resets::ResetScope;
+use roles::{Permission, Role, RoleGrants};
+use seasons::{SeasonPeriod, SeasonRecord, SeasonState};
+use status::{ConfigSource, ConfigSources, HandlerErrors};
+
 /// State of a single game for a user in a chat
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct GameState {
+    /// random id carried by inline buttons so stale ones can be recognized
+    pub id: u64,
     pub target: i32,
     pub attempts_left: i32,
     /// how many attempts this game started with (used to compute next game's
     /// starting attempts when a quick win occurs)
     pub start_attempts: i32,
+    /// guesses made so far, each with the interval known at that moment
+    pub guesses: Vec<Guess>,
+    /// bounds still possible given the "too low"/"too high" feedback so far
+    pub low: i32,
+    pub high: i32,
+}
+
+impl GameState {
+    /// New game with a random target in [min, max]
+    pub fn new(min: i32, max: i32, attempts: i32) -> Self {
+        GameState {
+            id: rand::random(),
+            target: rand_in_range(min, max),
+            attempts_left: attempts,
+            start_attempts: attempts,
+            guesses: Vec::new(),
+            low: min,
+            high: max,
+        }
+    }
+
+    /// Record a guess against the bounds known so far, then narrow the bounds
+    /// with the feedback the user is about to receive.
+    pub fn record_guess(&mut self, value: i32) {
+        self.guesses.push(Guess {
+            value,
+            low: self.low,
+            high: self.high,
+        });
+        if value < self.target {
+            self.low = std::cmp::max(self.low, value + 1);
+        } else if value > self.target {
+            self.high = std::cmp::min(self.high, value - 1);
+        }
+    }
+
+    /// Whether a "Play again" button sent when the game `game_id` ended may
+    /// restart this game, the user's current one: it must be that finished
+    /// game, or a new one without guesses yet. A game already being played
+    /// is never thrown away.
+    pub fn accepts_play_again(&self, game_id: u64) -> bool {
+        self.id == game_id || self.guesses.is_empty()
+    }
+
+    /// Efficiency rating of the guesses made so far
+    pub fn rating(&self) -> GameRating {
+        rating::rate_game(&self.guesses, self.target)
+    }
 }
 
 /// Shared application state
+#[derive(Default)]
 pub struct AppState {
     // key: (chat_id, user_id)
     pub by_user: HashMap<(i64, u64), GameState>,
     // language preferences
     pub user_langs: HashMap<(i64, u64), Lang>,
-    pub chat_langs: HashMap<i64, Lang>,
+    // group-level preferences (data/chat_settings.json), key: chat_id
+    pub chat_settings: HashMap<i64, ChatSettings>,
+    // Telegram chat administrators, refreshed every CHAT_ADMINS_TTL_SECS
+    pub chat_admins: AdminCache,
     // users already shown the welcome prompt (per chat) -> unix timestamp
     pub seen_welcome: HashMap<String, u64>,
     // persisted map of "chat:user" -> start_attempts for the next/new game
     pub user_start_attempts: HashMap<String, i32>,
     // persisted map of "chat:user" -> consecutive non-quick-win losses
     pub user_miss_streaks: HashMap<String, i32>,
+    // persisted map of "chat:user" -> wins that decremented the start attempts
+    // since the last reset (capped at NUMBER_ATTEMPTS)
+    pub user_win_streaks: HashMap<String, i32>,
+    // persisted map of "chat:user" -> lifetime game statistics
+    pub user_stats: HashMap<String, UserStats>,
+    // current season scores and boundaries (data/season.json)
+    pub season: SeasonState,
+    // finished seasons with their champions (data/season_archive.json)
+    pub season_archive: Vec<SeasonRecord>,
+    // pending /cancella_dati requests: requester -> (target user, unix timestamp)
+    pub pending_erasures: HashMap<u64, (u64, u64)>,
+    // pending global /reset_starts requests: requester -> unix timestamp
+    pub pending_resets: HashMap<u64, u64>,
+    // guess being composed on the inline keypad, key: (chat_id, user_id)
+    pub keypad_input: HashMap<(i64, u64), String>,
+    // global admins and chat moderators granted with /ruoli (data/roles.json)
+    pub role_grants: RoleGrants,
+    // privileged commands and their outcome, oldest first (data/audit_log.jsonl)
+    pub audit_log: Vec<AuditEntry>,
 }
 
 pub type SharedState = Arc<RwLock<AppState>>;
@@ -50,18 +163,231 @@ pub struct Config {
     // Environment variable: NUMBER_ATTEMPTS
     pub restart_threshold: i32,
     pub lang: Lang,
-    pub messages: HashMap<String, Messages>,
+    // reloadable at runtime with /reload_messages
+    pub messages: MessageStore,
     pub ttl_seconds: u64,
     pub bot_owner_id: Option<u64>,
     // set of "chat:user" strings allowed to call /reset_starts (from RESET_USER_STARTS)
     pub reset_user_starts: HashSet<String>,
+    // length of a season (SEASON_PERIOD); None disables seasons
+    pub season_period: Option<SeasonPeriod>,
+    // tag -> language tried when no file matches the tag (built-ins + LANG_ALIASES)
+    pub lang_aliases: HashMap<String, Lang>,
+    // token buckets applied to every update (RATE_LIMIT_* and THROTTLE_NOTICE_SECS)
+    pub rate_limits: RateLimits,
+    // where each setting read at startup came from, shown by /status
+    pub config_sources: BTreeMap<&'static str, ConfigSource>,
+    // chats served (OPERATING_MODE), unless changed with /modalita
+    pub operating_mode: OperatingMode,
+    // chats always allowed in allowlist mode (ALLOWED_CHATS)
+    pub allowed_chats: BTreeSet<i64>,
+    // leave groups that are refused (LEAVE_REFUSED_CHATS)
+    pub leave_refused_chats: bool,
+}
+
+impl Config {
+    /// The game rules of chats without `/imposta` overrides
+    pub fn game_rules(&self) -> GameRules {
+        GameRules {
+            min: self.min,
+            max: self.max,
+            attempts: self.attempts,
+            restart_threshold: self.restart_threshold,
+        }
+    }
+}
+
+/// The game rules of a chat: the configuration with the chat's overrides
+pub fn chat_rules(state: &AppState, config: &Config, chat_id: i64) -> GameRules {
+    match state.chat_settings.get(&chat_id) {
+        Some(settings) => settings.game_rules(config.game_rules()),
+        None => config.game_rules(),
+    }
+}
+
+/// The values used when the environment sets nothing, with no messages
+/// loaded (handy for tests).
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            min: 1,
+            max: 100,
+            attempts: 5,
+            restart_threshold: 3,
+            lang: Lang::en(),
+            messages: MessageStore::default(),
+            ttl_seconds: 60 * 60 * 24 * 30,
+            bot_owner_id: None,
+            reset_user_starts: HashSet::new(),
+            season_period: Some(SeasonPeriod::Monthly),
+            lang_aliases: lang::default_lang_aliases(),
+            rate_limits: RateLimits::default(),
+            config_sources: BTreeMap::new(),
+            operating_mode: OperatingMode::Public,
+            allowed_chats: BTreeSet::new(),
+            leave_refused_chats: false,
+        }
+    }
 }
 
 pub type SharedConfig = Arc<Config>;
 
+/// Runtime state kept outside of `AppState`: the checks run on every update
+/// before it reaches the game or takes the state lock, and the diagnostics
+/// of `/status`. Behind plain locks: they are never held across an `.await`.
+#[derive(Debug)]
+pub struct Guards {
+    pub rate_limiter: std::sync::Mutex<RateLimiter>,
+    // users ignored entirely, globally or per chat (data/bans.json)
+    pub bans: std::sync::RwLock<BanList>,
+    // unix timestamp maintenance mode was turned on (data/maintenance.json)
+    pub maintenance: std::sync::RwLock<Option<u64>>,
+    // operating mode and chats set by the owner at runtime (data/access.json)
+    pub access: std::sync::RwLock<AccessList>,
+    // unix timestamp of the start, for the uptime
+    pub started_at: u64,
+    // errors returned by the handlers, counted in run_bot
+    pub errors: std::sync::Mutex<HandlerErrors>,
+    // handler errors forwarded to the owner, and the repeats held back
+    pub alerts: std::sync::Mutex<ErrorAlerts>,
+}
+
+impl Guards {
+    pub fn new(
+        config: &Config,
+        bans: BanList,
+        maintenance: Option<u64>,
+        access: AccessList,
+    ) -> Self {
+        Guards {
+            rate_limiter: std::sync::Mutex::new(RateLimiter::new(config.rate_limits.clone())),
+            bans: std::sync::RwLock::new(bans),
+            maintenance: std::sync::RwLock::new(maintenance),
+            access: std::sync::RwLock::new(access),
+            started_at: now_unix(),
+            errors: std::sync::Mutex::new(HandlerErrors::default()),
+            alerts: std::sync::Mutex::new(ErrorAlerts::default()),
+        }
+    }
+
+    /// Count an error returned by a handler
+    pub fn record_error(&self) {
+        self.errors
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .record(Instant::now());
+    }
+
+    /// Since when the bot is in maintenance mode, if it is
+    pub fn maintenance_since(&self) -> Option<u64> {
+        *self
+            .maintenance
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Turn maintenance mode on or off and persist it; `false` if it
+    /// already was in that mode
+    fn set_maintenance(&self, on: bool) -> bool {
+        let since = {
+            let mut maintenance = self
+                .maintenance
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if maintenance.is_some() == on {
+                return false;
+            }
+            *maintenance = on.then(now_unix);
+            *maintenance
+        };
+        broadcast::save_maintenance(&Path::new("data").join("maintenance.json"), since);
+        true
+    }
+
+    /// Whether updates of `user_id` in `chat_id` must be ignored
+    pub fn is_banned(&self, chat_id: i64, user_id: u64) -> bool {
+        self.bans
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .is_banned(chat_id, user_id, now_unix())
+    }
+
+    /// Change the ban list and persist it to `data/bans.json`; expired bans
+    /// are dropped on the way
+    fn update_bans<T>(&self, change: impl FnOnce(&mut BanList) -> T) -> T {
+        let (result, snapshot) = {
+            let mut bans = self
+                .bans
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            bans.remove_expired(now_unix());
+            (change(&mut bans), bans.clone())
+        };
+        bans::save_bans(&Path::new("data").join("bans.json"), &snapshot);
+        result
+    }
+
+    /// Whether the bot serves a chat in the operating mode in force. The
+    /// owner is always served, so they can change the mode from anywhere.
+    pub fn access_check(
+        &self,
+        config: &Config,
+        chat_id: i64,
+        is_private: bool,
+        user_id: Option<u64>,
+    ) -> Result<(), Refusal> {
+        if user_id.is_some() && user_id == config.bot_owner_id {
+            return Ok(());
+        }
+        self.access
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .check(
+                config.operating_mode,
+                &config.allowed_chats,
+                chat_id,
+                is_private,
+            )
+    }
+
+    /// A copy of the runtime access list
+    pub fn access_list(&self) -> AccessList {
+        self.access
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Change the access list and persist it to `data/access.json`
+    fn update_access<T>(&self, change: impl FnOnce(&mut AccessList) -> T) -> T {
+        let (result, snapshot) = {
+            let mut access = self
+                .access
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            (change(&mut access), access.clone())
+        };
+        access::save_access(&Path::new("data").join("access.json"), &snapshot);
+        result
+    }
+
+    /// Take a token for an update of `user_id` in `chat_id`
+    pub fn rate_check(&self, chat_id: i64, user_id: Option<u64>) -> Verdict {
+        self.rate_limiter
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .check(chat_id, user_id, Instant::now())
+    }
+}
+
+pub type SharedGuards = Arc<Guards>;
+
 /// Messages container loaded from JSON files per language
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Messages {
+    /// language tag these messages were loaded for (selects the plural rules)
+    #[serde(skip)]
+    pub tag: String,
     pub cannot_start: String,
     pub cannot_guess: String,
     pub game_started: String,
@@ -80,72 +406,424 @@ pub struct Messages {
     pub language_name: String,
     pub reset_starts_ok: String,
     pub success_correct: String,
+    pub season_standings: String,
+    pub season_standings_line: String,
+    pub season_empty: String,
+    pub season_champions: String,
+    pub season_champion_line: String,
+    pub season_ended: String,
+    pub season_ended_no_winner: String,
+    pub seasons_disabled: String,
+    pub game_rating: String,
+    pub stats: String,
+    pub stats_empty: String,
+    pub data_export_caption: String,
+    pub data_export_sent: String,
+    pub data_export_private_failed: String,
+    pub data_erase_confirm: String,
+    pub data_erase_done: String,
+    pub data_erase_no_pending: String,
+    pub data_erase_admin_confirm: String,
+    pub data_erase_admin_done: String,
+    pub data_erase_usage: String,
+    pub button_play_again: String,
+    pub button_stale: String,
+    pub button_not_yours: String,
+    pub keypad_prompt: String,
+    pub keypad_prompt_empty: String,
+    pub lang_chat_admin_only: String,
+    pub not_authorized: String,
+    pub current_language: String,
+    pub language_entry: String,
+    pub available_languages: String,
+    pub messages_reloaded: String,
+    pub messages_reload_failed: String,
+    pub roles_list: String,
+    pub roles_none: String,
+    pub role_owner: String,
+    pub role_global_admin: String,
+    pub role_chat_admin: String,
+    pub role_moderator: String,
+    pub role_player: String,
+    pub role_granted: String,
+    pub role_revoked: String,
+    pub role_unchanged: String,
+    pub roles_usage: String,
+    pub reset_user_done: String,
+    pub reset_chat_done: String,
+    pub reset_all_confirm: String,
+    pub reset_all_no_pending: String,
+    pub reset_me_done: String,
+    pub reset_usage: String,
+    pub audit_header: String,
+    pub audit_entry: String,
+    pub audit_empty: String,
+    pub audit_usage: String,
+    pub audit_anonymous: String,
+    pub audit_outcome_done: String,
+    pub audit_outcome_unchanged: String,
+    pub audit_outcome_pending: String,
+    pub audit_outcome_denied: String,
+    pub audit_outcome_failed: String,
+    pub rate_limited_user: String,
+    pub rate_limited_chat: String,
+    pub ban_done: String,
+    pub unban_done: String,
+    pub unban_not_banned: String,
+    pub ban_protected: String,
+    pub ban_usage: String,
+    pub bans_list: String,
+    pub bans_entry: String,
+    pub bans_none: String,
+    pub settings_updated: String,
+    pub settings_reset: String,
+    pub settings_usage: String,
+    pub settings_invalid_range: String,
+    pub settings_invalid_attempts: String,
+    pub settings_invalid_threshold: String,
+    pub config_chat_overrides: String,
+    pub maintenance_active: String,
+    pub maintenance_on: String,
+    pub maintenance_off: String,
+    pub maintenance_status_on: String,
+    pub maintenance_status_off: String,
+    pub broadcast_usage: String,
+    pub broadcast_started: String,
+    pub broadcast_done: String,
+    pub broadcast_failure: String,
+    pub broadcast_announcement: String,
+    pub status_header: String,
+    pub status_maps_header: String,
+    pub status_map_line: String,
+    pub status_files_header: String,
+    pub status_file_line: String,
+    pub status_no_files: String,
+    pub status_config_header: String,
+    pub status_config_line: String,
+    pub status_written_at: String,
+    pub status_not_written: String,
+    pub status_uptime: String,
+    pub status_size: String,
+    pub access_refused_allowlist: String,
+    pub access_refused_private: String,
+    pub access_mode_status: String,
+    pub access_mode_usage: String,
+    pub access_mode_set: String,
+    pub access_usage: String,
+    pub access_list: String,
+    pub access_chat_configured: String,
+    pub access_chat_changed: String,
+    pub error_alert: String,
+    pub error_digest: String,
+    pub error_digest_line: String,
+    pub error_digest_no_command: String,
 }
 
-/// Load a Messages struct from a given JSON file path, falling back to defaults
-pub fn load_messages_file(path: &str, lang: Lang) -> Messages {
-    match fs::read_to_string(path) {
-        Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
-            tracing::warn!("failed to parse {}: {}. Falling back to defaults.", path, e);
-            default_messages(lang)
-        }),
-        Err(e) => {
-            tracing::warn!("failed to read {}: {}. Falling back to defaults.", path, e);
-            default_messages(lang)
+/// Read the raw key/value pairs of a message file, or why it can't be read
+fn try_read_message_entries(
+    path: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    serde_json::from_str(&s).map_err(|e| format!("failed to parse {}: {}", path, e))
+}
+
+/// Read the raw key/value pairs of a message file. A missing or malformed
+/// file yields no entries, so every key falls back.
+fn read_message_entries(path: &str) -> serde_json::Map<String, serde_json::Value> {
+    try_read_message_entries(path).unwrap_or_else(|e| {
+        tracing::warn!("{}. Falling back to defaults.", e);
+        serde_json::Map::new()
+    })
+}
+
+/// Keep the command names of a right-to-left catalog readable: `/gioco`
+/// embedded in Arabic text is wrapped in a left-to-right isolate.
+fn isolate_rtl_commands(entries: &mut serde_json::Map<String, serde_json::Value>, lang: &Lang) {
+    if !bidi::is_rtl(lang.tag()) {
+        return;
+    }
+    for value in entries.values_mut() {
+        if let Some(s) = value.as_str() {
+            *value = serde_json::Value::String(bidi::isolate_commands(s));
         }
     }
 }
 
-/// Return default Messages for a given language; currently only English is supported.
-pub fn default_messages(lang: Lang) -> Messages {
-    match lang {
-    Lang::En => Messages {
-            cannot_start: "I can't start a game for channels or messages without a user.".to_string(),
-            cannot_guess: "I can't handle guesses without a user.".to_string(),
-            game_started: "🎯 Game started for you! Guess a number between {min} and {max}. Attempts left: {attempts}".to_string(),
-            config: "Current configuration:\nMinimum number = {min},\nMaximum number = {max},\nAttempts left = {attempts},\nFailure chances = {number_attempts},\nMaximum attempts for this game = {next_attempts}".to_string(),
-            welcome_prompt: "Hi {name}! Use /gioco to start your personal game,\n/config to see the current settings,\n/lang to see available languages.\nSet your language with /lang it, /lang en, /lang ru, /lang zh or /lang ar.".to_string(),
-            no_attempts: "No attempts left. Use /gioco to restart.".to_string(),
-            revealed: "❌ You've run out of attempts. The number was {target}. Use /gioco to restart. You have {number_attempts} possibilities left before failing and starting over.".to_string(),
-            too_low: "Too low. Attempts left: {attempts}".to_string(),
-            too_high: "Too high. Attempts left: {attempts}".to_string(),
-            lang_set_user: "Your language preference was set.".to_string(),
-            lang_set_chat: "Chat language preference was set.".to_string(),
-            lang_invalid: "Invalid usage. Correct examples: `/lang en`, `/lang it`, `/lang ar`, `/lang ru`, `/lang zh`.".to_string(),
-            pong: "pong".to_string(),
-            not_started_prompt: "Start the game. Use /gioco to begin.".to_string(),
-            current_language_label: "Current language:".to_string(),
-            language_name: "English".to_string(),
-            reset_starts_ok: "User's starting settings have been reset. Use /gioco to start again.".to_string(),
-            success_correct: "✅ You guessed it!! Guess a new random number in {next_attempts} attempts. You will have {number_attempts} possibilities before failing and starting over.".to_string(),
-        },
-    _ => default_messages(Lang::En),
+/// Build a `Messages` from partial catalogs in priority order; every key none
+/// of them provides comes from the English built-ins.
+pub fn merge_messages(layers: &[&serde_json::Map<String, serde_json::Value>]) -> Messages {
+    let serde_json::Value::Object(mut merged) =
+        serde_json::to_value(default_messages()).expect("built-in messages serialize")
+    else {
+        unreachable!("Messages serializes to an object");
+    };
+    for layer in layers.iter().rev() {
+        for (key, value) in layer.iter() {
+            if value.is_string() && merged.contains_key(key) {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
     }
+    serde_json::from_value(serde_json::Value::Object(merged))
+        .expect("built-in messages provide every key")
 }
 
-/// Load every `*.json` file from the `messages/` directory and return a map
-/// from language tag (Lang) to parsed `Messages` value. Files which fail to
-/// parse will fall back to defaults for that language.
-pub fn load_all_messages(dir: &str) -> HashMap<String, Messages> {
-    let mut map = HashMap::new();
+/// Keys of `Messages` that a partial catalog lacks (or has with a non-string
+/// value), sorted.
+pub fn missing_message_keys(entries: &serde_json::Map<String, serde_json::Value>) -> Vec<String> {
+    let serde_json::Value::Object(all) =
+        serde_json::to_value(default_messages()).expect("built-in messages serialize")
+    else {
+        unreachable!("Messages serializes to an object");
+    };
+    let mut missing: Vec<String> = all
+        .keys()
+        .filter(|k| !entries.get(*k).is_some_and(|v| v.is_string()))
+        .cloned()
+        .collect();
+    missing.sort_unstable();
+    missing
+}
+
+impl Messages {
+    /// Fill one of these messages' templates, resolving plural/select blocks
+    /// with the rules of their language.
+    pub fn format(&self, template: &str, pairs: &[(&str, &str)]) -> String {
+        plural::format_message(template, &self.tag, pairs)
+    }
+
+    /// Format an integer the way these messages' language writes numbers
+    pub fn number(&self, n: impl std::fmt::Display) -> String {
+        numbers::format_number(n, &self.tag)
+    }
+}
+
+/// Load a Messages struct from a given JSON file path; keys missing from the
+/// file fall back one by one to the defaults.
+pub fn load_messages_file(path: &str, lang: &Lang) -> Messages {
+    let mut entries = read_message_entries(path);
+    isolate_rtl_commands(&mut entries, lang);
+    let mut messages = merge_messages(&[&entries]);
+    messages.tag = lang.tag().to_string();
+    messages
+}
+
+/// Return the built-in English messages, the last step of every fallback
+/// chain.
+pub fn default_messages() -> Messages {
+    Messages {
+        tag: "en".to_string(),
+        cannot_start: "I can't start a game for channels or messages without a user.".to_string(),
+        cannot_guess: "I can't handle guesses without a user.".to_string(),
+        game_started: "🎯 Game started for you! Guess a number between {min} and {max}. You have {attempts, plural, one {# attempt} other {# attempts}}.".to_string(),
+        config: "Current configuration:\nMinimum number = {min},\nMaximum number = {max},\nAttempts left = {attempts},\nFailure chances = {number_attempts},\nMaximum attempts for this game = {next_attempts}".to_string(),
+        welcome_prompt: "Hi {name}! Use /gioco to start your personal game,\n/config to see the current settings,\n/lang to see available languages.\nSet your language with {lang_commands}.".to_string(),
+        no_attempts: "No attempts left. Use /gioco to restart.".to_string(),
+        revealed: "❌ You've run out of attempts. The number was {target}. Use /gioco to restart. {number_attempts, plural, =0 {One more failed game and your progress starts over.} one {You have # possibility left before failing and starting over.} other {You have # possibilities left before failing and starting over.}}".to_string(),
+        too_low: "Too low. Attempts left: {attempts}".to_string(),
+        too_high: "Too high. Attempts left: {attempts}".to_string(),
+        lang_set_user: "Your language preference was set.".to_string(),
+        lang_set_chat: "Chat language preference was set.".to_string(),
+        lang_invalid: "Invalid usage. Correct examples: {lang_commands}.".to_string(),
+        pong: "pong".to_string(),
+        not_started_prompt: "Start the game. Use /gioco to begin.".to_string(),
+        current_language_label: "Current language:".to_string(),
+        language_name: "English".to_string(),
+        reset_starts_ok: "User's starting settings have been reset. Use /gioco to start again.".to_string(),
+        success_correct: "✅ You guessed it!! Guess a new random number in {next_attempts, plural, one {# attempt} other {# attempts}}. {number_attempts, plural, =0 {Your starting attempts won't drop any further.} one {Further wins can still lower your starting attempts one more time.} other {Further wins can still lower your starting attempts # more times.}}".to_string(),
+        season_standings: "🏆 Season {season} standings (ends on {ends}):\n{standings}".to_string(),
+        season_standings_line: "{rank}. {name} — {points} points ({wins} wins)".to_string(),
+        season_empty: "🏆 Season {season} (ends on {ends}): nobody has won a game in this chat yet.".to_string(),
+        season_champions: "Past champions:\n{champions}".to_string(),
+        season_champion_line: "Season {season}: {name} — {points} points".to_string(),
+        season_ended: "🏁 Season {season} is over! The champion is {name} with {points} points ({wins} wins). Season {next_season} starts now and scores are reset.".to_string(),
+        season_ended_no_winner: "🏁 Season {season} is over! Nobody won a game in this chat. Season {next_season} starts now.".to_string(),
+        seasons_disabled: "Seasons are not enabled on this bot.".to_string(),
+        game_rating: "📊 Efficiency: {efficiency}% — {wasted} of {guesses} guesses were outside the still-possible range. Bisection would have needed {optimal} guesses.".to_string(),
+        stats: "📈 Your statistics:\nGames played = {games},\nWins = {wins},\nLosses = {losses},\nAverage efficiency = {efficiency}%,\nWasted guesses = {wasted} of {guesses}".to_string(),
+        stats_empty: "You haven't finished any games yet. Use /gioco to start.".to_string(),
+        data_export_caption: "📦 Everything this bot stores about you.".to_string(),
+        data_export_sent: "📦 I sent you your data in a private message.".to_string(),
+        data_export_private_failed: "I couldn't message you privately. Start a private chat with me and run /miei_dati there.".to_string(),
+        data_erase_confirm: "⚠️ This permanently erases your games, progress, statistics, season scores and preferences in every chat. Send /cancella_dati conferma within 5 minutes to confirm.".to_string(),
+        data_erase_done: "🗑️ Your data has been erased ({entries} entries).".to_string(),
+        data_erase_no_pending: "There is no pending deletion to confirm. Send /cancella_dati first.".to_string(),
+        data_erase_admin_confirm: "⚠️ This permanently erases all data of user {user}. Send /cancella_dati {user} conferma within 5 minutes to confirm.".to_string(),
+        data_erase_admin_done: "🗑️ The data of user {user} has been erased ({entries} entries).".to_string(),
+        data_erase_usage: "Usage: /cancella_dati, then /cancella_dati conferma to erase your data.".to_string(),
+        button_play_again: "🔁 Play again".to_string(),
+        button_stale: "This button belongs to a game that is already over.".to_string(),
+        button_not_yours: "This button belongs to someone else's game.".to_string(),
+        keypad_prompt: "🔢 Your guess: {input}".to_string(),
+        keypad_prompt_empty: "🔢 Compose your guess with the keypad and press ✅.".to_string(),
+        lang_chat_admin_only: "Only the administrators of this chat can change its language.".to_string(),
+        not_authorized: "Not authorized.".to_string(),
+        current_language: "{label} {language}".to_string(),
+        language_entry: "{name} ({tag})".to_string(),
+        available_languages: "Available languages: {languages}".to_string(),
+        messages_reloaded: "♻️ Messages reloaded: {count, plural, one {# language} other {# languages}} ({languages}).".to_string(),
+        messages_reload_failed: "⚠️ Messages not reloaded, the current ones stay in use:\n{problems}".to_string(),
+        roles_list: "👥 Roles\nOwner: {owner}\nGlobal admins: {admins}\nModerators of this chat: {moderators}\nThe Telegram administrators of this chat are chat admins.\nYour role: {role}".to_string(),
+        roles_none: "none".to_string(),
+        role_owner: "owner".to_string(),
+        role_global_admin: "global admin".to_string(),
+        role_chat_admin: "chat admin".to_string(),
+        role_moderator: "moderator".to_string(),
+        role_player: "player".to_string(),
+        role_granted: "✅ User {user} is now {role}.".to_string(),
+        role_revoked: "✅ User {user} is no longer {role}.".to_string(),
+        role_unchanged: "Nothing changed for user {user}.".to_string(),
+        roles_usage: "Usage: /ruoli to list the roles, /ruoli aggiungi <user_id> moderatore|admin to grant one, /ruoli rimuovi <user_id> moderatore|admin to revoke it (or reply to the user's message and omit the id).".to_string(),
+        reset_user_done: "🔄 The progression of user {user} in this chat has been reset.".to_string(),
+        reset_chat_done: "🔄 The progression of {count, plural, =0 {no player} one {# player} other {# players}} in this chat has been reset.".to_string(),
+        reset_all_confirm: "⚠️ This resets the progression of every user in every chat. Send /reset_starts conferma within 5 minutes to confirm.".to_string(),
+        reset_all_no_pending: "There is no pending reset to confirm. Send /reset_starts first.".to_string(),
+        reset_me_done: "🔄 Your progression in this chat has been reset: your next game starts with {attempts, plural, one {# attempt} other {# attempts}}.".to_string(),
+        reset_usage: "Usage: /reset_starts <user_id> (or in reply to a message) for one player of this chat, /reset_starts chat for this chat, /reset_starts for every chat. /reset_me resets your own progression.".to_string(),
+        audit_header: "📜 Audit log, page {page} of {pages} (newest first):".to_string(),
+        audit_entry: "{time} · chat {chat} · {actor}: {command} → {outcome}".to_string(),
+        audit_empty: "The audit log is empty.".to_string(),
+        audit_usage: "Usage: /audit [page], with a page from 1 to {pages}.".to_string(),
+        audit_anonymous: "anonymous".to_string(),
+        audit_outcome_done: "done".to_string(),
+        audit_outcome_unchanged: "unchanged".to_string(),
+        audit_outcome_pending: "awaiting confirmation".to_string(),
+        audit_outcome_denied: "denied".to_string(),
+        audit_outcome_failed: "failed".to_string(),
+        rate_limited_user: "⏳ You are sending messages too fast: slow down, the next ones will be ignored for a few seconds.".to_string(),
+        rate_limited_chat: "⏳ This chat is sending messages too fast: some of them will be ignored for a few seconds.".to_string(),
+        ban_done: "🚫 User {user} is now banned {scope, select, global {from every chat} other {from this chat}} {until, select, never {permanently} other {until {until} UTC}}: the bot will ignore their messages.".to_string(),
+        unban_done: "✅ User {user} is no longer banned {scope, select, global {from every chat} other {from this chat}}.".to_string(),
+        unban_not_banned: "User {user} is not banned {scope, select, global {from every chat} other {from this chat}}.".to_string(),
+        ban_protected: "You cannot ban user {user}: their role is not lower than yours.".to_string(),
+        ban_usage: "Usage: /ban <user_id> [globale] [30m|12h|7d|2w] [reason] and /unban <user_id> [globale]; in reply to a message the id can be omitted. /bannati [globale] lists the bans.".to_string(),
+        bans_list: "🚫 Banned users {scope, select, global {in every chat} other {in this chat}}:".to_string(),
+        bans_entry: "• {user}, {until, select, never {permanent} other {until {until} UTC}}, by {by}: {reason}".to_string(),
+        bans_none: "No banned users.".to_string(),
+        settings_updated: "⚙️ Game settings of this chat: numbers from {min} to {max}, {attempts, plural, one {# attempt} other {# attempts}}, progression reset after {threshold, plural, one {# lost game} other {# lost games}}. New games use them.".to_string(),
+        settings_reset: "⚙️ This chat uses the default game settings again.".to_string(),
+        settings_usage: "Usage: /imposta min <n> max <n> tentativi <n> soglia <n> (any of them, in any order) to change the game settings of this chat, /imposta predefiniti to go back to the defaults. Chat admins only.".to_string(),
+        settings_invalid_range: "Not saved: min ({min}) must be less than max ({max}).".to_string(),
+        settings_invalid_attempts: "Not saved: tentativi ({attempts}) must be a positive integer.".to_string(),
+        settings_invalid_threshold: "Not saved: soglia ({threshold}) must be a non-negative integer.".to_string(),
+        config_chat_overrides: "These values were set for this chat with /imposta.".to_string(),
+        maintenance_active: "🛠️ The bot is under maintenance and will be back soon. Running games are kept.".to_string(),
+        maintenance_on: "🛠️ Maintenance mode is on: /gioco, guesses and game buttons answer with a \"back soon\" message. Send /manutenzione off when done.".to_string(),
+        maintenance_off: "✅ Maintenance mode is off: games are open again.".to_string(),
+        maintenance_status_on: "🛠️ Maintenance mode is on since {since} UTC. Usage: /manutenzione on|off".to_string(),
+        maintenance_status_off: "Maintenance mode is off. Usage: /manutenzione on|off".to_string(),
+        broadcast_usage: "Usage: /annuncio <text>. Translations go on lines starting with a language tag, e.g. \"[it] Torniamo presto\"; chats whose language has no translation get the text before the first tag.".to_string(),
+        broadcast_started: "📢 Sending the announcement to {count, plural, =0 {no chat} one {# chat} other {# chats}}…".to_string(),
+        broadcast_done: "📢 Announcement delivered to {sent} of {count, plural, one {# chat} other {# chats}}; {failed, plural, =0 {no failures} one {# failure} other {# failures}}.".to_string(),
+        broadcast_failure: "• {chat}: {error}".to_string(),
+        broadcast_announcement: "📢 {text}".to_string(),
+        status_header: "📊 Bot status\nVersion: {version}\nUptime: {uptime} (since {since} UTC)\nActive games: {games}\nHandler errors: {recent} in the last hour, {total} since start".to_string(),
+        status_maps_header: "Persisted maps (entries):".to_string(),
+        status_map_line: "• {name}: {count}".to_string(),
+        status_files_header: "Data files:".to_string(),
+        status_file_line: "• {file}: {size}, {written}".to_string(),
+        status_no_files: "• no data files yet".to_string(),
+        status_config_header: "Configuration:".to_string(),
+        status_config_line: "• {name}: {source, select, environment {environment} dotenv {.env file} default {default} invalid {invalid value, default used} other {{source}}}".to_string(),
+        status_written_at: "last written {time} UTC".to_string(),
+        status_not_written: "last write unknown".to_string(),
+        status_uptime: "{span, select, days {{days}d {hours}h {minutes}m} hours {{hours}h {minutes}m} other {{minutes}m}}".to_string(),
+        status_size: "{unit, select, kib {{whole}.{tenths} KiB} mib {{whole}.{tenths} MiB} gib {{whole}.{tenths} GiB} other {{whole} B}}".to_string(),
+        access_refused_allowlist: "🔒 This bot is private and is not enabled in this chat.".to_string(),
+        access_refused_private: "🔒 This bot only works in private chats: write to me directly.".to_string(),
+        access_mode_status: "Operating mode: {mode, select, public {public, every chat} allowlist {allowed chats only} private_only {private chats only} other {{mode}}}{configured, select, yes { (from the configuration)} other { (set with /modalita)}}.\nUsage: /modalita pubblico|lista|privato|predefinita".to_string(),
+        access_mode_usage: "Usage: /modalita pubblico|lista|privato, or /modalita predefinita to follow OPERATING_MODE again.".to_string(),
+        access_mode_set: "{changed, select, no {Nothing changed: the operating mode} other {Operating mode set:}} {mode, select, public {public, every chat} allowlist {allowed chats only} private_only {private chats only} other {{mode}}}.".to_string(),
+        access_usage: "Usage: /chat_consentite aggiungi|rimuovi [chat_id]; without an id the current chat. /chat_consentite alone lists the allowed chats.".to_string(),
+        access_list: "Allowed chats (used in allowlist mode, now {mode, select, public {public} allowlist {allowlist} private_only {private only} other {{mode}}}):\n• added with /chat_consentite: {chats}\n• from ALLOWED_CHATS: {configured}".to_string(),
+        access_chat_configured: "Chat {chat} is allowed by ALLOWED_CHATS: remove it from the configuration instead.".to_string(),
+        access_chat_changed: "{changed, select, yes {{action, select, add {Chat {chat} is now allowed.} other {Chat {chat} is no longer allowed.}}} other {{action, select, add {Chat {chat} was already allowed.} other {Chat {chat} was not in the list.}}}}".to_string(),
+        error_alert: "⚠️ Handler error in chat {chat}\nCommand: {command}\n{error}".to_string(),
+        error_digest: "🧾 Error digest: {count, plural, one {# repeated error was} other {# repeated errors were}} not forwarded in the last day.".to_string(),
+        error_digest_line: "• {count}× {command}: {error}".to_string(),
+        error_digest_no_command: "no command".to_string(),
+    }
+}
+
+/// Messages loaded from the `messages/` directory
+pub struct MessageCatalog {
+    /// language tag -> resolved messages
+    pub messages: HashMap<String, Messages>,
+    /// language tag -> keys its file lacks, filled from the fallback chain
+    /// (only files with missing keys are listed)
+    pub missing_keys: BTreeMap<String, Vec<String>>,
+    /// language tag -> why its file could not be read or parsed; such a
+    /// language is loaded entirely from the fallbacks
+    pub unreadable: BTreeMap<String, String>,
+}
+
+/// Load every `*.json` file from `dir`. Each key missing from a file falls
+/// back individually to the file of `default_lang` and then to the English
+/// built-ins; the keys each file lacks are collected for the startup report.
+pub fn load_message_catalog(dir: &str, default_lang: &Lang) -> MessageCatalog {
+    let mut raw = HashMap::new();
+    let mut unreadable = BTreeMap::new();
     let p = Path::new(dir);
     if let Ok(entries) = p.read_dir() {
         for entry in entries.flatten() {
-            if let Ok(fname) = entry.file_name().into_string() {
-                if fname.to_lowercase().ends_with(".json") {
-                    let stem = fname.trim_end_matches(".json");
-                    if let Some(lang) = parse_lang(stem) {
-                        let path = format!("{}/{}", dir, fname);
-                        let msgs = load_messages_file(&path, lang);
-                        map.insert(lang_tag(&lang).to_string(), msgs);
-                    } else {
-                        tracing::warn!("skipping unknown language file: {}", fname);
-                    }
+            if let Ok(fname) = entry.file_name().into_string()
+                && fname.to_lowercase().ends_with(".json")
+            {
+                let stem = fname.trim_end_matches(".json");
+                if let Some(lang) = parse_lang(stem) {
+                    let path = format!("{}/{}", dir, fname);
+                    let mut entries = try_read_message_entries(&path).unwrap_or_else(|e| {
+                        tracing::warn!("{}. Falling back to defaults.", e);
+                        unreadable.insert(lang.tag().to_string(), e);
+                        serde_json::Map::new()
+                    });
+                    isolate_rtl_commands(&mut entries, &lang);
+                    raw.insert(lang.tag().to_string(), entries);
+                } else {
+                    tracing::warn!("skipping language file without a BCP-47 name: {}", fname);
                 }
             }
         }
     }
-    map
+    let empty = serde_json::Map::new();
+    let default_entries = raw.get(default_lang.tag()).unwrap_or(&empty);
+    let mut catalog = MessageCatalog {
+        messages: HashMap::new(),
+        missing_keys: BTreeMap::new(),
+        unreadable,
+    };
+    for (tag, entries) in raw.iter() {
+        let missing = missing_message_keys(entries);
+        if !missing.is_empty() {
+            catalog.missing_keys.insert(tag.clone(), missing);
+        }
+        let mut messages = merge_messages(&[entries, default_entries]);
+        messages.tag = tag.clone();
+        catalog.messages.insert(tag.clone(), messages);
+    }
+    catalog
+}
+
+/// Language tags of the `<tag>.json` message files in `dir`, to resolve
+/// DEFAULT_LANG before the catalog is loaded
+pub fn message_file_tags(dir: &str) -> HashSet<String> {
+    let Ok(entries) = Path::new(dir).read_dir() else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|f| f.to_lowercase().ends_with(".json"))
+        .filter_map(|f| parse_lang(f.trim_end_matches(".json")))
+        .map(|l| l.tag().to_string())
+        .collect()
+}
+
+/// Load every `*.json` file from the `messages/` directory and return a map
+/// from language tag to parsed `Messages` value. Keys missing from a
+/// file fall back to the English built-ins.
+pub fn load_all_messages(dir: &str) -> HashMap<String, Messages> {
+    load_message_catalog(dir, &Lang::en()).messages
 }
 
 /// Simple template formatter: replace `{key}` with `value` for each pair in `pairs`.
@@ -171,10 +849,7 @@ fn load_seen_welcome(path: &Path) -> HashMap<String, u64> {
         return HashMap::new();
     }
     match fs::read_to_string(path) {
-        Ok(s) => match serde_json::from_str::<HashMap<String, u64>>(&s) {
-            Ok(m) => m,
-            Err(_) => HashMap::new(),
-        },
+        Ok(s) => serde_json::from_str::<HashMap<String, u64>>(&s).unwrap_or_default(),
         Err(_) => HashMap::new(),
     }
 }
@@ -184,8 +859,10 @@ fn save_seen_welcome(path: &Path, map: &HashMap<String, u64>) {
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
-    if let Ok(s) = serde_json::to_string_pretty(map) {
-        let _ = fs::write(path, s);
+    if let Ok(s) = serde_json::to_string_pretty(map)
+        && fs::write(path, s).is_ok()
+    {
+        status::record_write(path);
     }
 }
 
@@ -195,10 +872,7 @@ fn load_user_start_attempts(path: &Path) -> HashMap<String, i32> {
         return HashMap::new();
     }
     match fs::read_to_string(path) {
-        Ok(s) => match serde_json::from_str::<HashMap<String, i32>>(&s) {
-            Ok(m) => m,
-            Err(_) => HashMap::new(),
-        },
+        Ok(s) => serde_json::from_str::<HashMap<String, i32>>(&s).unwrap_or_default(),
         Err(_) => HashMap::new(),
     }
 }
@@ -208,8 +882,10 @@ fn save_user_start_attempts(path: &Path, map: &HashMap<String, i32>) {
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
-    if let Ok(s) = serde_json::to_string_pretty(map) {
-        let _ = fs::write(path, s);
+    if let Ok(s) = serde_json::to_string_pretty(map)
+        && fs::write(path, s).is_ok()
+    {
+        status::record_write(path);
     }
 }
 
@@ -219,10 +895,7 @@ fn load_user_miss_streaks(path: &Path) -> HashMap<String, i32> {
         return HashMap::new();
     }
     match fs::read_to_string(path) {
-        Ok(s) => match serde_json::from_str::<HashMap<String, i32>>(&s) {
-            Ok(m) => m,
-            Err(_) => HashMap::new(),
-        },
+        Ok(s) => serde_json::from_str::<HashMap<String, i32>>(&s).unwrap_or_default(),
         Err(_) => HashMap::new(),
     }
 }
@@ -232,41 +905,33 @@ fn save_user_miss_streaks(path: &Path, map: &HashMap<String, i32>) {
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
-    if let Ok(s) = serde_json::to_string_pretty(map) {
-        let _ = fs::write(path, s);
+    if let Ok(s) = serde_json::to_string_pretty(map)
+        && fs::write(path, s).is_ok()
+    {
+        status::record_write(path);
     }
 }
 
-/// Supported languages
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum Lang {
-    En,
-    It,
-    Ar,
-    Ru,
-    Zh,
-}
-
-/// Parse a short language tag into `Lang`.
-pub fn parse_lang(s: &str) -> Option<Lang> {
-    match s.to_lowercase().as_str() {
-        "it" => Some(Lang::It),
-        "en" => Some(Lang::En),
-        "ar" => Some(Lang::Ar),
-        "ru" => Some(Lang::Ru),
-        "zh" => Some(Lang::Zh),
-        _ => None,
+/// Persisted per-user win streaks helpers
+fn load_user_win_streaks(path: &Path) -> HashMap<String, i32> {
+    if !path.exists() {
+        return HashMap::new();
+    }
+    match fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str::<HashMap<String, i32>>(&s).unwrap_or_default(),
+        Err(_) => HashMap::new(),
     }
 }
 
-/// Return the short tag for a Lang variant (e.g. Lang::En -> "en").
-fn lang_tag(l: &Lang) -> &'static str {
-    match l {
-        Lang::En => "en",
-        Lang::It => "it",
-        Lang::Ar => "ar",
-        Lang::Ru => "ru",
-        Lang::Zh => "zh",
+/// Save the user_win_streaks map to the given path as pretty JSON
+fn save_user_win_streaks(path: &Path, map: &HashMap<String, i32>) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(s) = serde_json::to_string_pretty(map)
+        && fs::write(path, s).is_ok()
+    {
+        status::record_write(path);
     }
 }
 
@@ -279,284 +944,2351 @@ pub fn rand_in_range(min: i32, max: i32) -> i32 {
 }
 
 /// Compute how many attempts the next game should have after a successful
-/// guess. New behavior: every win reduces the starting attempts for the next
-/// game by one (to make subsequent wins harder), never going below 1.
-/// The `restart_threshold` is still used elsewhere for reset-on-misses logic
-/// but is not involved here.
-pub fn next_attempts_after_win(
+/// guess. A win reduces the starting attempts for the next game by one
+/// (never going below 1), but only for the first `restart_threshold` wins
+/// since the last reset: once `win_streak` (wins already counted before this
+/// one) reaches the threshold, the start attempts stay where they are until
+/// the miss-streak reset restores `GAME_ATTEMPTS`.
+pub fn next_attempts_for_win_streak(
     previous_start_attempts: i32,
-    _remaining_after_guess: i32,
-    _restart_threshold: i32,
+    win_streak: i32,
+    restart_threshold: i32,
 ) -> i32 {
-    std::cmp::max(1, previous_start_attempts - 1)
+    if win_streak >= restart_threshold {
+        previous_start_attempts
+    } else {
+        std::cmp::max(1, previous_start_attempts - 1)
+    }
 }
 
-/// Determine the effective language for a message, given the shared state
-pub async fn effective_lang(state: &SharedState, msg: &Message, default: Lang) -> Lang {
-    let chat_id = msg.chat.id.0;
-    let lock = state.read().await;
-    if let Some(user) = msg.from.as_ref() {
-        let key = (chat_id, user.id.0);
-        if let Some(&l) = lock.user_langs.get(&key) {
-            return l;
-        }
-    }
-    if let Some(&l) = lock.chat_langs.get(&chat_id) {
-        return l;
-    }
-    // If the Telegram user provided a language_code, try to respect it for new users
-    if let Some(user) = msg.from.as_ref() {
-        if let Some(lang_code) = user.language_code.as_ref() {
-            // parse_lang expects short tags like "en", "it", etc.
-            if let Some(parsed) = parse_lang(lang_code) {
-                return parsed;
-            }
-            // sometimes language_code can be full locale like "en-US"; try prefix
-            if lang_code.len() >= 2 {
-                let prefix = &lang_code[..2];
-                if let Some(parsed) = parse_lang(prefix) {
-                    return parsed;
-                }
-            }
-        }
+/// The win streak after a game, given the miss streak after it: a win
+/// extends it up to `restart_threshold`; a loss keeps it, so decrements stay
+/// paused, until the miss streak reaches the threshold and the reset starts
+/// the count over.
+pub fn win_streak_after_game(
+    won: bool,
+    win_streak: i32,
+    miss_streak: i32,
+    restart_threshold: i32,
+) -> i32 {
+    if won {
+        std::cmp::min(win_streak + 1, restart_threshold)
+    } else if miss_streak >= restart_threshold {
+        0
+    } else {
+        win_streak
     }
+}
 
-    default
+/// How many more wins will still decrement the start attempts, given the
+/// win streak after the latest win.
+pub fn remaining_decrements(win_streak: i32, restart_threshold: i32) -> i32 {
+    std::cmp::max(0, restart_threshold - win_streak)
 }
 
-/// Test helper: determine effective language given simple parts (used by tests)
-pub async fn effective_lang_from_parts(
+/// Language of the replies to `user_id` in `chat_id`: the user's `/lang`
+/// choice, the chat's language, the Telegram `language_code` and
+/// `config.lang`, negotiated against the loaded messages.
+pub async fn effective_lang(
     state: &SharedState,
-    user_language_code: Option<&str>,
-    user_id: Option<u64>,
+    config: &Config,
     chat_id: i64,
-    default: Lang,
+    user_id: Option<u64>,
+    language_code: Option<&str>,
 ) -> Lang {
     let lock = state.read().await;
-    if let Some(uid) = user_id {
-        let key = (chat_id, uid);
-        if let Some(&l) = lock.user_langs.get(&key) {
-            return l;
-        }
+    let prefs = LangPreferences {
+        user: user_id.and_then(|uid| lock.user_langs.get(&(chat_id, uid))),
+        chat: lock
+            .chat_settings
+            .get(&chat_id)
+            .and_then(|s| s.lang.as_ref()),
+        language_code,
+    };
+    let all = config.messages.snapshot();
+    negotiate_lang(prefs, &config.lang_aliases, &config.lang, |t| {
+        all.contains_key(t)
+    })
+}
+
+/// A `/lang` argument resolved against the loaded messages with the
+/// configured aliases
+fn requested_lang(config: &Config, code: &str) -> Option<Lang> {
+    let all = config.messages.snapshot();
+    lookup_lang(&parse_lang(code)?, &config.lang_aliases, |t| {
+        all.contains_key(t)
+    })
+}
+
+/// Match a requested language against the loaded messages along its lookup
+/// chain, without aliases (so `en-US` selects `en.json`).
+pub fn supported_lang(messages: &HashMap<String, Messages>, code: &str) -> Option<Lang> {
+    lookup_lang(&parse_lang(code)?, &HashMap::new(), |t| {
+        messages.contains_key(t)
+    })
+}
+
+/// The `/lang <tag>` commands for every loaded language, for the hints in
+/// `welcome_prompt` and `lang_invalid`.
+fn lang_commands(config: &Config) -> String {
+    let all = config.messages.snapshot();
+    let mut tags: Vec<&String> = all.keys().collect();
+    tags.sort_unstable();
+    tags.iter()
+        .map(|t| format!("/lang {}", t))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Role of a user in a chat known without asking Telegram: `BOT_OWNER_ID`
+/// and the roles granted with `/ruoli`.
+fn stored_role(config: &Config, grants: &RoleGrants, chat_id: i64, user_id: u64) -> Option<Role> {
+    if config.bot_owner_id == Some(user_id) {
+        return Some(Role::Owner);
     }
-    if let Some(&l) = lock.chat_langs.get(&chat_id) {
-        return l;
+    grants.granted_role(chat_id, user_id)
+}
+
+/// Whether `permission` is allowed without asking Telegram. A "chat:user"
+/// pair listed in `RESET_USER_STARTS` predates roles and only allows
+/// `/reset_starts` (everybody's progression) in that chat, as it always did.
+pub fn stored_permission(
+    config: &Config,
+    grants: &RoleGrants,
+    chat_id: i64,
+    user_id: u64,
+    permission: Permission,
+) -> bool {
+    if stored_role(config, grants, chat_id, user_id) >= Some(permission.required_role()) {
+        return true;
     }
-    drop(lock);
-    if let Some(lang_code) = user_language_code {
-        if let Some(parsed) = parse_lang(lang_code) {
-            return parsed;
+    permission == Permission::ResetStarts
+        && config
+            .reset_user_starts
+            .contains(&format!("{}:{}", chat_id, user_id))
+}
+
+/// Whether a user administers a chat on Telegram (anyone does in a private
+/// chat). Administrators are fetched with `getChatAdministrators` and cached.
+async fn is_telegram_chat_admin(
+    bot: &Bot,
+    state: &SharedState,
+    chat: &teloxide::types::Chat,
+    user_id: u64,
+) -> bool {
+    if chat.is_private() {
+        return true;
+    }
+    let now = now_unix();
+    if let Some(admins) = state.read().await.chat_admins.get(chat.id.0, now) {
+        return admins.contains(&user_id);
+    }
+    match bot.get_chat_administrators(chat.id).await {
+        Ok(members) => {
+            let admins: HashSet<u64> = members.iter().map(|m| m.user.id.0).collect();
+            let allowed = admins.contains(&user_id);
+            state
+                .write()
+                .await
+                .chat_admins
+                .insert(chat.id.0, admins, now);
+            allowed
         }
-        if lang_code.len() >= 2 {
-            let prefix = &lang_code[..2];
-            if let Some(parsed) = parse_lang(prefix) {
-                return parsed;
-            }
+        Err(e) => {
+            tracing::warn!(
+                "failed to fetch administrators of chat {}: {}",
+                chat.id.0,
+                e
+            );
+            false
         }
     }
-    default
 }
 
-/// Handle an incoming message, updating state as needed and sending replies.
-async fn handle_message(
+/// Highest role of a user in a chat; Telegram is only asked when the stored
+/// roles are below chat admin.
+async fn user_role(
+    bot: &Bot,
+    state: &SharedState,
+    config: &Config,
+    chat: &teloxide::types::Chat,
+    user_id: u64,
+) -> Option<Role> {
+    let stored = stored_role(config, &state.read().await.role_grants, chat.id.0, user_id);
+    if stored >= Some(Role::ChatAdmin) {
+        return stored;
+    }
+    if is_telegram_chat_admin(bot, state, chat, user_id).await {
+        return Some(Role::ChatAdmin);
+    }
+    stored
+}
+
+/// The permission check of every privileged command: whether the user's
+/// role in the chat is at least the one `permission` requires.
+async fn authorize(
     bot: &Bot,
+    state: &SharedState,
+    config: &Config,
+    chat: &teloxide::types::Chat,
+    user_id: u64,
+    permission: Permission,
+) -> bool {
+    let stored = {
+        let lock = state.read().await;
+        stored_permission(config, &lock.role_grants, chat.id.0, user_id, permission)
+    };
+    if stored {
+        return true;
+    }
+    permission.required_role() <= Role::ChatAdmin
+        && is_telegram_chat_admin(bot, state, chat, user_id).await
+}
+
+/// [`authorize`] for the sender of a message; anonymous senders have no role
+async fn sender_may(
+    bot: &Bot,
+    state: &SharedState,
+    config: &Config,
     msg: &Message,
+    permission: Permission,
+) -> bool {
+    match msg.from.as_ref() {
+        Some(user) => authorize(bot, state, config, &msg.chat, user.id.0, permission).await,
+        None => false,
+    }
+}
+
+/// Whether `text` is the command `name`, such as `/audit`, possibly
+/// addressed as `/audit@botname` and followed by arguments. Unlike a prefix
+/// match, `/auditx` is not `/audit`.
+pub fn is_command(text: &str, name: &str) -> bool {
+    text.split_whitespace().next().is_some_and(|word| {
+        let command = word.split_once('@').map_or(word, |(c, _)| c);
+        command.eq_ignore_ascii_case(name)
+    })
+}
+
+/// Localized name of a role; users without one are players
+fn role_name(messages: &Messages, role: Option<Role>) -> &str {
+    match role {
+        Some(Role::Owner) => &messages.role_owner,
+        Some(Role::GlobalAdmin) => &messages.role_global_admin,
+        Some(Role::ChatAdmin) => &messages.role_chat_admin,
+        Some(Role::Moderator) => &messages.role_moderator,
+        None => &messages.role_player,
+    }
+}
+
+/// User ids for the `/ruoli` listing, or `roles_none`
+fn id_list<'a>(ids: impl Iterator<Item = &'a u64>, messages: &Messages) -> String {
+    let ids: Vec<String> = ids.map(u64::to_string).collect();
+    if ids.is_empty() {
+        messages.roles_none.clone()
+    } else {
+        ids.join(", ")
+    }
+}
+
+/// The `/ruoli` listing of a chat for a user whose role is `role`
+pub fn roles_listing(
+    messages: &Messages,
+    owner: Option<u64>,
+    grants: &RoleGrants,
+    chat_id: i64,
+    role: Option<Role>,
+) -> String {
+    let owner = owner
+        .map(|o| o.to_string())
+        .unwrap_or_else(|| messages.roles_none.clone());
+    messages.format(
+        &messages.roles_list,
+        &[
+            ("owner", &owner),
+            ("admins", &id_list(grants.global_admins.iter(), messages)),
+            (
+                "moderators",
+                &id_list(
+                    grants.moderators.get(&chat_id).into_iter().flatten(),
+                    messages,
+                ),
+            ),
+            ("role", role_name(messages, role)),
+        ],
+    )
+}
+
+/// Persist the progression maps, after a reset
+fn save_progress(state: &AppState) {
+    let data_dir = Path::new("data");
+    save_user_start_attempts(
+        &data_dir.join("user_start_attempts.json"),
+        &state.user_start_attempts,
+    );
+    save_user_miss_streaks(
+        &data_dir.join("user_miss_streaks.json"),
+        &state.user_miss_streaks,
+    );
+    save_user_win_streaks(
+        &data_dir.join("user_win_streaks.json"),
+        &state.user_win_streaks,
+    );
+}
+
+/// Persist every map stored under `data/`. Used after changes that touch
+/// several maps at once, such as erasing a user's data.
+fn save_all_data(state: &AppState) {
+    let data_dir = Path::new("data");
+    save_seen_welcome(&data_dir.join("seen_welcome.json"), &state.seen_welcome);
+    save_user_start_attempts(
+        &data_dir.join("user_start_attempts.json"),
+        &state.user_start_attempts,
+    );
+    save_user_miss_streaks(
+        &data_dir.join("user_miss_streaks.json"),
+        &state.user_miss_streaks,
+    );
+    save_user_win_streaks(
+        &data_dir.join("user_win_streaks.json"),
+        &state.user_win_streaks,
+    );
+    rating::save_user_stats(&data_dir.join("user_stats.json"), &state.user_stats);
+    seasons::save_season(&data_dir.join("season.json"), &state.season);
+    seasons::save_season_archive(&data_dir.join("season_archive.json"), &state.season_archive);
+    roles::save_role_grants(&data_dir.join("roles.json"), &state.role_grants);
+}
+
+/// Record a privileged command and its outcome in the audit log, in memory
+/// and appended to `data/audit_log.jsonl`
+fn audit(state: &mut AppState, msg: &Message, outcome: AuditOutcome) {
+    let entry = AuditEntry::new(
+        now_unix(),
+        msg.chat.id.0,
+        msg.from.as_ref().map(|u| u.id.0),
+        msg.text().unwrap_or_default(),
+        outcome,
+    );
+    audit::append_audit_entry(&Path::new("data").join("audit_log.jsonl"), &entry);
+    state.audit_log.push(entry);
+}
+
+/// Longest error chain put in a notification, well below Telegram's limit
+const ALERT_ERROR_MAX_CHARS: usize = 1500;
+
+/// Repeats listed in the daily digest (the count covers all of them)
+const DIGEST_LINES_SHOWN: usize = 20;
+
+/// Longest error chain of a digest line
+const DIGEST_ERROR_MAX_CHARS: usize = 150;
+
+/// Forward a handler error to the owner's private chat, unless it repeats
+/// an error of the day or the hourly limit is reached
+async fn notify_owner_of_error(
+    bot: &Bot,
+    state: &SharedState,
+    config: &Config,
+    guards: &Guards,
+    chat_id: i64,
+    command: Option<String>,
+    err: &anyhow::Error,
+) {
+    let Some(owner) = config.bot_owner_id else {
+        return;
+    };
+    let chain = format!("{:#}", err);
+    let key = AlertKey {
+        command: command.clone().unwrap_or_default(),
+        error: chain.clone(),
+    };
+    let alert = guards
+        .alerts
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .on_error(key, Instant::now());
+    if alert == Alert::Suppress {
+        return;
+    }
+    let lang = effective_lang(state, config, owner as i64, Some(owner), None).await;
+    let messages = &messages_for(config, &lang);
+    let mut error: String = chain.chars().take(ALERT_ERROR_MAX_CHARS).collect();
+    if error.len() < chain.len() {
+        error.push('…');
+    }
+    let text = messages.format(
+        &messages.error_alert,
+        &[
+            ("chat", &chat_id.to_string()),
+            (
+                "command",
+                command
+                    .as_deref()
+                    .unwrap_or(&messages.error_digest_no_command),
+            ),
+            ("error", &error),
+        ],
+    );
+    if let Err(e) = bot.send_message(ChatId(owner as i64), text).await {
+        tracing::warn!("error notification not sent to the owner: {}", e);
+    }
+}
+
+/// Send the owner, every day at 00:00 UTC, the handler errors whose repeats
+/// were not forwarded
+async fn run_error_digest(
+    bot: Bot,
     state: SharedState,
     config: SharedConfig,
-) -> Result<()> {
-    let lang = effective_lang(&state, msg, config.lang).await;
-    // pick messages by language from the map, fall back to config.lang then to English
-    // look up messages by the language tag (strings), falling back to config.lang then to English
-    let messages = config
-        .messages
-        .get(lang_tag(&lang))
-        .or_else(|| config.messages.get(lang_tag(&config.lang)))
-        .or_else(|| config.messages.get("en"))
-        .expect("there should always be at least English messages available");
-
-    if let Some(text) = msg.text() {
-        let text = text.trim();
-        if text.eq_ignore_ascii_case("/reset_starts") {
-            // only allow if BOT_OWNER_ID matches the sender (if configured) or
-            // if the composite "chat:user" is present in RESET_USER_STARTS
-            let allowed = if let Some(user) = msg.from.as_ref() {
-                let is_owner = config.bot_owner_id.map(|o| user.id.0 == o).unwrap_or(false);
-                let composite = format!("{}:{}", msg.chat.id.0, user.id.0);
-                let in_reset_list = config.reset_user_starts.contains(&composite);
-                is_owner || in_reset_list
-            } else {
-                false
-            };
-            if !allowed {
-                // silently ignore or respond with not permitted; choose to respond
-                bot.send_message(msg.chat.id, "Not authorized.").await?;
-                return Ok(());
-            }
-            let mut lock = state.write().await;
-            lock.user_start_attempts.clear();
-            // also clear miss streaks so remaining-before-reset goes back to full
-            lock.user_miss_streaks.clear();
-            let data_path = Path::new("data").join("user_start_attempts.json");
-            let _ = save_user_start_attempts(&data_path, &lock.user_start_attempts);
-            let miss_path = Path::new("data").join("user_miss_streaks.json");
-            let _ = save_user_miss_streaks(&miss_path, &lock.user_miss_streaks);
-            bot.send_message(msg.chat.id, messages.reset_starts_ok.clone())
-                .await?;
-            return Ok(());
-        }
-        if text.eq_ignore_ascii_case("/ping") {
-            bot.send_message(msg.chat.id, messages.pong.clone()).await?;
-            return Ok(());
+    guards: SharedGuards,
+) {
+    let Some(owner) = config.bot_owner_id else {
+        return;
+    };
+    loop {
+        let wait = alerts::secs_until_next_digest(now_unix());
+        tokio::time::sleep(std::time::Duration::from_secs(wait)).await;
+        let digest = guards
+            .alerts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take_digest();
+        if digest.is_empty() {
+            continue;
         }
-
-        if text.eq_ignore_ascii_case("/gioco") {
-            let user_id = match msg.from.as_ref().map(|u| u.id.0) {
-                Some(id) => id,
-                None => {
-                    bot.send_message(msg.chat.id, messages.cannot_start.clone())
-                        .await?;
-                    return Ok(());
-                }
-            };
-            let mut lock = state.write().await;
-            let key = (msg.chat.id.0, user_id);
-            // consult persisted per-user start attempts (if any)
-            let composite = format!("{}:{}", msg.chat.id.0, user_id);
-            let start_attempts = lock
-                .user_start_attempts
-                .get(&composite)
-                .copied()
-                .unwrap_or(config.attempts);
-            let new_game = GameState {
-                target: rand_in_range(config.min, config.max),
-                attempts_left: start_attempts,
-                start_attempts,
+        let lang = effective_lang(&state, &config, owner as i64, Some(owner), None).await;
+        let messages = &messages_for(&config, &lang);
+        let total: u64 = digest.iter().map(|s| s.count).sum();
+        let mut text = messages.format(
+            &messages.error_digest,
+            &[("count", &messages.number(total))],
+        );
+        for suppressed in digest.iter().take(DIGEST_LINES_SHOWN) {
+            let command = if suppressed.key.command.is_empty() {
+                messages.error_digest_no_command.as_str()
+            } else {
+                suppressed.key.command.as_str()
             };
-            lock.by_user.insert(key, new_game.clone());
-            // persist the chosen start_attempts for this user so future games (and restarts)
-            // will use the same starting value until changed by a win
-            lock.user_start_attempts
-                .insert(composite.clone(), start_attempts);
-            let data_path = Path::new("data").join("user_start_attempts.json");
-            let _ = save_user_start_attempts(&data_path, &lock.user_start_attempts);
-            let reply = format_with(
-                &messages.game_started,
+            let error: String = suppressed
+                .key
+                .error
+                .chars()
+                .take(DIGEST_ERROR_MAX_CHARS)
+                .collect();
+            text.push('\n');
+            text.push_str(&messages.format(
+                &messages.error_digest_line,
                 &[
-                    ("min", &config.min.to_string()),
-                    ("max", &config.max.to_string()),
-                    ("attempts", &new_game.attempts_left.to_string()),
+                    ("count", &messages.number(suppressed.count)),
+                    ("command", command),
+                    ("error", &error),
                 ],
-            );
-            bot.send_message(msg.chat.id, reply).await?;
-            return Ok(());
+            ));
+        }
+        if digest.len() > DIGEST_LINES_SHOWN {
+            text.push_str(&format!("\n• … (+{})", digest.len() - DIGEST_LINES_SHOWN));
         }
+        tracing::info!("error digest: {} repeats held back", total);
+        if let Err(e) = bot.send_message(ChatId(owner as i64), text).await {
+            tracing::warn!("error digest not sent to the owner: {}", e);
+        }
+    }
+}
 
-        if text.to_lowercase().starts_with("/lang") {
-            let parts: Vec<&str> = text.split_whitespace().collect();
-            let mut lock = state.write().await;
-            if parts.len() == 1 {
-                // reply using a localized label and build the current language dynamically
-                // keys are language tags (String). We'll build a richer display below.
-                // Build the current language display: label + space + "Name (tag)" using the loaded Messages
-                // messages for the effective language provide `language_name` and `current_language_label`.
-                let current = format!(
-                    "{} {} ({})",
-                    messages.current_language_label,
-                    messages.language_name,
-                    lang_tag(&lang)
-                );
-                let mut reply = current;
-                reply.push_str("\n");
-                // Build the available languages display: iterate over keys but show name+tag where possible
-                let mut available_items: Vec<String> = config
-                    .messages
-                    .iter()
-                    .map(|(k, v)| format!("{} ({})", v.language_name, k))
-                    .collect();
-                available_items.sort_unstable();
-                let available = available_items.join(", ");
-                reply.push_str(&format!("Available languages: {}", available));
-                bot.send_message(msg.chat.id, reply).await?;
-                return Ok(());
-            }
-            if parts.len() == 2 {
-                if let Some(new_lang) = crate::parse_lang(parts[1]) {
-                    if let Some(user) = msg.from.as_ref() {
-                        let key = (msg.chat.id.0, user.id.0);
-                        lock.user_langs.insert(key, new_lang);
-                        bot.send_message(msg.chat.id, messages.lang_set_user.clone())
-                            .await?;
-                        return Ok(());
-                    } else {
-                        bot.send_message(msg.chat.id, messages.cannot_start.clone())
+/// The `/status` report: version, uptime, games, persisted data, handler
+/// errors and where each setting comes from
+async fn status_report(
+    state: &SharedState,
+    config: &Config,
+    guards: &Guards,
+    messages: &Messages,
+) -> String {
+    let now = now_unix();
+    let (recent_errors, total_errors) = {
+        let mut errors = guards
+            .errors
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        (errors.recent(Instant::now()), errors.total())
+    };
+    let (games, maps) = {
+        let lock = state.read().await;
+        let bans = guards
+            .bans
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        (lock.by_user.len(), status::map_sizes(&lock, &bans))
+    };
+    let mut report = messages.format(
+        &messages.status_header,
+        &[
+            ("version", env!("CARGO_PKG_VERSION")),
+            (
+                "uptime",
+                &status::format_uptime(now.saturating_sub(guards.started_at), messages),
+            ),
+            ("since", &audit::format_timestamp(guards.started_at)),
+            ("games", &messages.number(games)),
+            ("recent", &messages.number(recent_errors)),
+            ("total", &messages.number(total_errors)),
+        ],
+    );
+    report.push_str("\n\n");
+    report.push_str(&messages.status_maps_header);
+    for (name, count) in maps {
+        report.push('\n');
+        report.push_str(&messages.format(
+            &messages.status_map_line,
+            &[("name", name), ("count", &messages.number(count))],
+        ));
+    }
+    report.push_str("\n\n");
+    report.push_str(&messages.status_files_header);
+    let files = status::data_files(Path::new("data"));
+    if files.is_empty() {
+        report.push('\n');
+        report.push_str(&messages.status_no_files);
+    }
+    for file in files {
+        let written = match file.written {
+            Some(ts) => messages.format(
+                &messages.status_written_at,
+                &[("time", &audit::format_timestamp(ts))],
+            ),
+            None => messages.status_not_written.clone(),
+        };
+        report.push('\n');
+        report.push_str(&messages.format(
+            &messages.status_file_line,
+            &[
+                ("file", &file.name),
+                ("size", &status::format_size(file.size, messages)),
+                ("written", &written),
+            ],
+        ));
+    }
+    report.push_str("\n\n");
+    report.push_str(&messages.status_config_header);
+    for (name, source) in &config.config_sources {
+        let source = match source {
+            ConfigSource::Environment => "environment",
+            ConfigSource::DotEnv => "dotenv",
+            ConfigSource::Default => "default",
+            ConfigSource::Invalid => "invalid",
+        };
+        report.push('\n');
+        report.push_str(&messages.format(
+            &messages.status_config_line,
+            &[("name", name), ("source", source)],
+        ));
+    }
+    report
+}
+
+/// Pause between two messages of a broadcast, below Telegram's limit of
+/// about 30 messages per second
+const BROADCAST_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Failures listed in the broadcast report (the count covers all of them)
+const BROADCAST_FAILURES_SHOWN: usize = 10;
+
+/// Send an announcement to every chat in its language, one message every
+/// [`BROADCAST_INTERVAL`] (waiting when Telegram asks to retry later), then
+/// report deliveries and failures to the chat that asked for it.
+async fn run_broadcast(
+    bot: Bot,
+    state: SharedState,
+    config: SharedConfig,
+    announcement: Announcement,
+    chats: std::collections::BTreeSet<i64>,
+    report_chat: ChatId,
+    report_lang: Lang,
+) {
+    let mut sent = 0usize;
+    let mut failures: Vec<(i64, String)> = Vec::new();
+    for &chat in &chats {
+        // in a private chat the user's own preference applies
+        let private_user = u64::try_from(chat).ok();
+        let lang = effective_lang(&state, &config, chat, private_user, None).await;
+        let messages = &messages_for(&config, &lang);
+        let text = messages.format(
+            &messages.broadcast_announcement,
+            &[("text", announcement.text_for(&lang, &config.lang_aliases))],
+        );
+        let mut result = bot.send_message(ChatId(chat), text.clone()).await;
+        if let Err(teloxide::RequestError::RetryAfter(wait)) = &result {
+            tokio::time::sleep(wait.duration()).await;
+            result = bot.send_message(ChatId(chat), text).await;
+        }
+        match result {
+            Ok(_) => sent += 1,
+            Err(err) => {
+                tracing::warn!("broadcast to chat {} failed: {}", chat, err);
+                failures.push((chat, err.to_string()));
+            }
+        }
+        tokio::time::sleep(BROADCAST_INTERVAL).await;
+    }
+    tracing::info!("broadcast delivered to {} of {} chats", sent, chats.len());
+    let messages = &messages_for(&config, &report_lang);
+    let mut report = messages.format(
+        &messages.broadcast_done,
+        &[
+            ("sent", &messages.number(sent)),
+            ("count", &messages.number(chats.len())),
+            ("failed", &messages.number(failures.len())),
+        ],
+    );
+    for (chat, err) in failures.iter().take(BROADCAST_FAILURES_SHOWN) {
+        report.push('\n');
+        report.push_str(&messages.format(
+            &messages.broadcast_failure,
+            &[("chat", &chat.to_string()), ("error", err)],
+        ));
+    }
+    if failures.len() > BROADCAST_FAILURES_SHOWN {
+        report.push_str(&format!(
+            "\n• … (+{})",
+            failures.len() - BROADCAST_FAILURES_SHOWN
+        ));
+    }
+    if let Err(err) = bot.send_message(report_chat, report).await {
+        tracing::error!("broadcast report not sent: {}", err);
+    }
+}
+
+/// Localized explanation of invalid `/imposta` values
+fn rules_error(messages: &Messages, e: RulesError) -> String {
+    match e {
+        RulesError::MinNotBelowMax { min, max } => messages.format(
+            &messages.settings_invalid_range,
+            &[
+                ("min", &messages.number(min)),
+                ("max", &messages.number(max)),
+            ],
+        ),
+        RulesError::AttemptsNotPositive(a) => messages.format(
+            &messages.settings_invalid_attempts,
+            &[("attempts", &messages.number(a))],
+        ),
+        RulesError::NegativeThreshold(t) => messages.format(
+            &messages.settings_invalid_threshold,
+            &[("threshold", &messages.number(t))],
+        ),
+    }
+}
+
+/// Selector of the `{scope, select, ...}` blocks of the ban messages
+fn ban_scope_key(scope: BanScope) -> &'static str {
+    match scope {
+        BanScope::Global => "global",
+        BanScope::Chat(_) => "chat",
+    }
+}
+
+/// Selector and value of the `{until, select, ...}` blocks: `never` or the
+/// expiry in UTC
+fn ban_expiry(ban: &Ban) -> String {
+    ban.until
+        .map(audit::format_timestamp)
+        .unwrap_or_else(|| "never".to_string())
+}
+
+/// One line of `/bannati`
+fn ban_line(messages: &Messages, user_id: u64, ban: &Ban) -> String {
+    messages.format(
+        &messages.bans_entry,
+        &[
+            ("user", &user_id.to_string()),
+            ("until", &ban_expiry(ban)),
+            ("by", &ban.by.to_string()),
+            ("reason", ban.reason.as_deref().unwrap_or("-")),
+        ],
+    )
+}
+
+/// The `/bannati` listing: the bans in force in `scope` at `now`
+pub fn bans_listing(messages: &Messages, bans: &BanList, scope: BanScope, now: u64) -> String {
+    let active = bans.active(scope, now);
+    if active.is_empty() {
+        return messages.bans_none.clone();
+    }
+    let mut reply = messages.format(&messages.bans_list, &[("scope", ban_scope_key(scope))]);
+    for (user_id, ban) in active {
+        reply.push('\n');
+        reply.push_str(&ban_line(messages, user_id, ban));
+    }
+    reply
+}
+
+/// The notice sent the first time an update is dropped by a rate limit
+fn throttle_notice(messages: &Messages, limited: Limited) -> &String {
+    match limited {
+        Limited::User => &messages.rate_limited_user,
+        Limited::Chat => &messages.rate_limited_chat,
+    }
+}
+
+/// Localized refusal for a chat outside of the operating mode
+fn refusal_text(messages: &Messages, refusal: Refusal) -> &String {
+    match refusal {
+        Refusal::NotAllowed => &messages.access_refused_allowlist,
+        Refusal::NotPrivate => &messages.access_refused_private,
+    }
+}
+
+/// The chats of an access list, or "none"
+fn chat_list(chats: &BTreeSet<i64>, messages: &Messages) -> String {
+    if chats.is_empty() {
+        messages.roles_none.clone()
+    } else {
+        chats
+            .iter()
+            .map(i64::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Localized name of an audit outcome
+fn audit_outcome_name(messages: &Messages, outcome: AuditOutcome) -> &str {
+    match outcome {
+        AuditOutcome::Done => &messages.audit_outcome_done,
+        AuditOutcome::Unchanged => &messages.audit_outcome_unchanged,
+        AuditOutcome::Pending => &messages.audit_outcome_pending,
+        AuditOutcome::Denied => &messages.audit_outcome_denied,
+        AuditOutcome::Failed => &messages.audit_outcome_failed,
+    }
+}
+
+/// Entries shown by each page of `/audit`
+const AUDIT_PAGE_SIZE: usize = 10;
+
+/// How long a `/cancella_dati` request waits for its confirmation
+const ERASE_CONFIRM_WINDOW_SECS: u64 = 5 * 60;
+
+/// How long a global `/reset_starts` waits for its confirmation
+const RESET_CONFIRM_WINDOW_SECS: u64 = 5 * 60;
+
+/// Pick the messages for a language from the loaded catalogs along its
+/// lookup chain (aliases included), falling back to `config.lang` and then
+/// to English.
+fn messages_for(config: &Config, lang: &Lang) -> Messages {
+    let all = config.messages.snapshot();
+    let found = lookup_lang(lang, &config.lang_aliases, |t| all.contains_key(t))
+        .unwrap_or_else(|| config.lang.clone());
+    all.get(found.tag())
+        .or_else(|| all.get("en"))
+        .cloned()
+        .expect("there should always be at least English messages available")
+}
+
+/// Fill the `game_rating` template of `messages` with a finished game's rating
+pub fn format_rating(messages: &Messages, rating: &GameRating) -> String {
+    messages.format(
+        &messages.game_rating,
+        &[
+            ("efficiency", &messages.number(rating.efficiency)),
+            ("wasted", &messages.number(rating.wasted)),
+            ("guesses", &messages.number(rating.guesses)),
+            ("optimal", &messages.number(rating.optimal_guesses)),
+        ],
+    )
+}
+
+/// The `/lang` listing in the language of `messages`: the reply text and the
+/// `(tag, button label)` pairs of the picker, one per loaded language sorted
+/// by tag
+pub fn language_listing(
+    messages: &Messages,
+    languages: &HashMap<String, Messages>,
+) -> (String, Vec<(String, String)>) {
+    let mut entries: Vec<(String, String)> = languages
+        .iter()
+        .map(|(k, v)| {
+            let entry = messages.format(
+                &messages.language_entry,
+                &[("name", &v.language_name), ("tag", k)],
+            );
+            (k.clone(), entry)
+        })
+        .collect();
+    entries.sort_unstable();
+    let current = messages.format(
+        &messages.language_entry,
+        &[("name", &messages.language_name), ("tag", &messages.tag)],
+    );
+    let mut reply = messages.format(
+        &messages.current_language,
+        &[
+            ("label", &messages.current_language_label),
+            ("language", &current),
+        ],
+    );
+    reply.push('\n');
+    let available: Vec<&str> = entries.iter().map(|(_, e)| e.as_str()).collect();
+    reply.push_str(&messages.format(
+        &messages.available_languages,
+        &[("languages", &available.join(", "))],
+    ));
+    (reply, entries)
+}
+
+/// Directory of the `<tag>.json` message files, read at startup and by
+/// `/reload_messages`
+const MESSAGES_DIR: &str = "messages";
+/// Maximum number of problems listed in a failed `/reload_messages` reply
+const RELOAD_PROBLEMS_SHOWN: usize = 15;
+
+/// Maximum number of rows shown by `/stagione` for the current standings
+const SEASON_STANDINGS_ROWS: usize = 10;
+/// Maximum number of past champions shown by `/stagione`
+const SEASON_CHAMPIONS_ROWS: usize = 5;
+
+/// Build the `/stagione` reply for a chat: current standings followed by the
+/// champions of the most recent past seasons in that chat.
+pub fn season_report(
+    season: &SeasonState,
+    archive: &[SeasonRecord],
+    chat_id: i64,
+    messages: &Messages,
+) -> String {
+    let season_s = messages.number(season.number);
+    let ends = seasons::format_date(season.ends_at);
+    let standings = season.standings(chat_id);
+    let mut reply = if standings.is_empty() {
+        messages.format(
+            &messages.season_empty,
+            &[("season", &season_s), ("ends", &ends)],
+        )
+    } else {
+        let lines: Vec<String> = standings
+            .iter()
+            .take(SEASON_STANDINGS_ROWS)
+            .enumerate()
+            .map(|(i, (_, score))| {
+                messages.format(
+                    &messages.season_standings_line,
+                    &[
+                        ("rank", &messages.number(i + 1)),
+                        ("name", &score.name),
+                        ("points", &messages.number(score.points)),
+                        ("wins", &messages.number(score.wins)),
+                    ],
+                )
+            })
+            .collect();
+        messages.format(
+            &messages.season_standings,
+            &[
+                ("season", &season_s),
+                ("ends", &ends),
+                ("standings", &lines.join("\n")),
+            ],
+        )
+    };
+    let champions: Vec<String> = archive
+        .iter()
+        .rev()
+        .filter_map(|record| {
+            record.champions.get(&chat_id).map(|c| {
+                messages.format(
+                    &messages.season_champion_line,
+                    &[
+                        ("season", &messages.number(record.number)),
+                        ("name", &c.name),
+                        ("points", &messages.number(c.points)),
+                    ],
+                )
+            })
+        })
+        .take(SEASON_CHAMPIONS_ROWS)
+        .collect();
+    if !champions.is_empty() {
+        reply.push_str("\n\n");
+        reply.push_str(&messages.format(
+            &messages.season_champions,
+            &[("champions", &champions.join("\n"))],
+        ));
+    }
+    reply
+}
+
+/// Build the end-of-season announcement sent to a chat that had activity.
+pub fn season_end_announcement(
+    record: &SeasonRecord,
+    next_season: u32,
+    chat_id: i64,
+    messages: &Messages,
+) -> String {
+    let season_s = messages.number(record.number);
+    let next_s = messages.number(next_season);
+    match record.champions.get(&chat_id) {
+        Some(c) => messages.format(
+            &messages.season_ended,
+            &[
+                ("season", &season_s),
+                ("next_season", &next_s),
+                ("name", &c.name),
+                ("points", &messages.number(c.points)),
+                ("wins", &messages.number(c.wins)),
+            ],
+        ),
+        None => messages.format(
+            &messages.season_ended_no_winner,
+            &[("season", &season_s), ("next_season", &next_s)],
+        ),
+    }
+}
+
+/// How often the season scheduler checks whether the current season is over
+const SEASON_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Background task started by `run_bot`: when the season boundary is reached
+/// archive the season, reset the scores and announce the champion in every
+/// chat that played during the season.
+async fn run_season_scheduler(bot: Bot, state: SharedState, config: SharedConfig) {
+    let Some(period) = config.season_period else {
+        return;
+    };
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(SEASON_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let now = now_unix();
+        let (record, next_season, chat_langs) = {
+            let mut lock = state.write().await;
+            if !lock.season.is_over(now) {
+                continue;
+            }
+            let record = lock.season.close(period, now);
+            lock.season_archive.push(record.clone());
+            let data_dir = Path::new("data");
+            seasons::save_season(&data_dir.join("season.json"), &lock.season);
+            seasons::save_season_archive(
+                &data_dir.join("season_archive.json"),
+                &lock.season_archive,
+            );
+            let chat_langs: HashMap<i64, Lang> = record
+                .active_chats
+                .iter()
+                .filter_map(|c| {
+                    let lang = lock.chat_settings.get(c)?.lang.clone()?;
+                    Some((*c, lang))
+                })
+                .collect();
+            (record, lock.season.number, chat_langs)
+        };
+        tracing::info!(
+            "season {} closed: {} active chats, {} champions",
+            record.number,
+            record.active_chats.len(),
+            record.champions.len()
+        );
+        for chat_id in &record.active_chats {
+            let lang = chat_langs.get(chat_id).unwrap_or(&config.lang);
+            let messages = &messages_for(&config, lang);
+            let text = season_end_announcement(&record, next_season, *chat_id, messages);
+            if let Err(e) = bot.send_message(ChatId(*chat_id), text).await {
+                tracing::warn!("failed to announce season end in chat {}: {}", chat_id, e);
+            }
+        }
+    }
+}
+
+/// Start a new game for a user in a chat (`/gioco` and the "Play again"
+/// button), using the persisted start attempts for that user.
+async fn start_game(
+    bot: &Bot,
+    chat_id: ChatId,
+    user_id: u64,
+    state: &SharedState,
+    config: &Config,
+    messages: &Messages,
+) -> Result<()> {
+    let mut lock = state.write().await;
+    let rules = chat_rules(&lock, config, chat_id.0);
+    let key = (chat_id.0, user_id);
+    // consult persisted per-user start attempts (if any); a chat that
+    // lowered its attempts caps the progression earned before
+    let composite = format!("{}:{}", chat_id.0, user_id);
+    let start_attempts = lock
+        .user_start_attempts
+        .get(&composite)
+        .copied()
+        .unwrap_or(rules.attempts)
+        .min(rules.attempts);
+    let new_game = GameState::new(rules.min, rules.max, start_attempts);
+    lock.by_user.insert(key, new_game.clone());
+    // persist the chosen start_attempts for this user so future games (and restarts)
+    // will use the same starting value until changed by a win
+    lock.user_start_attempts
+        .insert(composite.clone(), start_attempts);
+    let data_path = Path::new("data").join("user_start_attempts.json");
+    save_user_start_attempts(&data_path, &lock.user_start_attempts);
+    if config.season_period.is_some() && lock.season.mark_active(chat_id.0) {
+        let season_path = Path::new("data").join("season.json");
+        seasons::save_season(&season_path, &lock.season);
+    }
+    let reply = messages.format(
+        &messages.game_started,
+        &[
+            ("min", &messages.number(rules.min)),
+            ("max", &messages.number(rules.max)),
+            ("attempts", &messages.number(new_game.attempts_left)),
+        ],
+    );
+    drop(lock);
+    bot.send_message(chat_id, reply).await?;
+    Ok(())
+}
+
+/// Apply a guess to the user's game (typed or composed on the keypad) and
+/// send the feedback.
+async fn process_guess(
+    bot: &Bot,
+    chat_id: ChatId,
+    user: &User,
+    guess: i32,
+    state: &SharedState,
+    config: &Config,
+    messages: &Messages,
+) -> Result<()> {
+    let mut lock = state.write().await;
+    let rules = chat_rules(&lock, config, chat_id.0);
+    let user_id = user.id.0;
+    let key = (chat_id.0, user_id);
+    if let Some(mut game) = lock.by_user.remove(&key) {
+        // check if no attempts left before decrement
+        if game.attempts_left == 0 {
+            // reinsert and notify
+            lock.by_user.insert(key, game);
+            bot.send_message(chat_id, messages.no_attempts.clone())
+                .await?;
+            return Ok(());
+        }
+
+        // decrement owned game attempts and track the guess for the rating
+        game.attempts_left = game.attempts_left.saturating_sub(1);
+        game.record_guess(guess);
+
+        if guess == game.target {
+            // compute attempts for the next game: the decrement only
+            // applies while the win streak is below the cap
+            let composite = format!("{}:{}", chat_id.0, user_id);
+            let win_streak = lock.user_win_streaks.get(&composite).copied().unwrap_or(0);
+            let next_attempts = next_attempts_for_win_streak(
+                game.start_attempts,
+                win_streak,
+                rules.restart_threshold,
+            );
+            let new_win_streak =
+                win_streak_after_game(true, win_streak, 0, rules.restart_threshold);
+            tracing::info!(
+                "win: chat={} user={} prev_start={} remaining_after_guess={} win_streak={} next= {}",
+                chat_id.0,
+                user_id,
+                game.start_attempts,
+                game.attempts_left,
+                new_win_streak,
+                next_attempts
+            );
+            // format success message with next_attempts and number_attempts placeholders
+            // Fill placeholders correctly:
+            // - `{next_attempts}` = computed next_attempts (e.g. GAME_ATTEMPTS - 1)
+            // - `{number_attempts}` = decrements still available before the cap
+            let mut success_msg = messages.format(
+                &messages.success_correct,
+                &[
+                    ("next_attempts", &messages.number(next_attempts)),
+                    (
+                        "number_attempts",
+                        &messages.number(remaining_decrements(
+                            new_win_streak,
+                            rules.restart_threshold,
+                        )),
+                    ),
+                ],
+            );
+            let game_rating = game.rating();
+            lock.user_stats
+                .entry(composite.clone())
+                .or_default()
+                .record(true, &game_rating);
+            success_msg.push('\n');
+            success_msg.push_str(&format_rating(messages, &game_rating));
+
+            // score the win for the current season before the game is replaced;
+            // serialized here, written once the lock is released
+            let season_json = if config.season_period.is_some() {
+                let name = user.first_name.clone();
+                let points = seasons::win_points(game.attempts_left);
+                lock.season.record_win(chat_id.0, user_id, &name, points);
+                seasons::season_json(&lock.season)
+            } else {
+                None
+            };
+
+            // reset game to next_attempts; the button stays bound to the
+            // game just won
+            let play_again =
+                keyboards::play_again_keyboard(&messages.button_play_again, user_id, game.id);
+            game = GameState::new(rules.min, rules.max, next_attempts);
+
+            // reset miss streak on a win, record the win streak and persist
+            lock.user_miss_streaks.insert(composite.clone(), 0);
+            lock.user_win_streaks
+                .insert(composite.clone(), new_win_streak);
+            lock.user_start_attempts
+                .insert(composite.clone(), next_attempts);
+            // clone maps for persisting
+            let starts_clone = lock.user_start_attempts.clone();
+            let misses_clone = lock.user_miss_streaks.clone();
+            let wins_clone = lock.user_win_streaks.clone();
+            let stats_clone = lock.user_stats.clone();
+            // reinsert updated game then drop lock to persist
+            lock.by_user.insert(key, game);
+            drop(lock);
+            bot.send_message(chat_id, success_msg)
+                .reply_markup(play_again)
+                .await?;
+
+            // Perform disk I/O operations without holding lock
+            let data_path = Path::new("data").join("user_start_attempts.json");
+            save_user_start_attempts(&data_path, &starts_clone);
+            let miss_path = Path::new("data").join("user_miss_streaks.json");
+            save_user_miss_streaks(&miss_path, &misses_clone);
+            let win_path = Path::new("data").join("user_win_streaks.json");
+            save_user_win_streaks(&win_path, &wins_clone);
+            let stats_path = Path::new("data").join("user_stats.json");
+            rating::save_user_stats(&stats_path, &stats_clone);
+            if let Some(json) = season_json {
+                let season_path = Path::new("data").join("season.json");
+                seasons::write_season(&season_path, &json);
+            }
+        } else {
+            if game.attempts_left == 0 {
+                // user ran out of attempts -> increment miss streak and persist
+                let composite = format!("{}:{}", chat_id.0, user_id);
+                let streak = lock.user_miss_streaks.get(&composite).copied().unwrap_or(0) + 1;
+                lock.user_miss_streaks.insert(composite.clone(), streak);
+                // the win streak only starts over with the reset below
+                let win_streak = lock.user_win_streaks.get(&composite).copied().unwrap_or(0);
+                match win_streak_after_game(false, win_streak, streak, rules.restart_threshold) {
+                    0 => lock.user_win_streaks.remove(&composite),
+                    kept => lock.user_win_streaks.insert(composite.clone(), kept),
+                };
+                if streak >= rules.restart_threshold {
+                    // reset condition: full attempts again
+                    lock.user_start_attempts
+                        .insert(composite.clone(), rules.attempts);
+                    lock.user_miss_streaks.insert(composite.clone(), 0);
+                }
+                let starts_clone = lock.user_start_attempts.clone();
+                let misses_clone = lock.user_miss_streaks.clone();
+                let game_rating = game.rating();
+                lock.user_stats
+                    .entry(composite.clone())
+                    .or_default()
+                    .record(false, &game_rating);
+                let wins_clone = lock.user_win_streaks.clone();
+                let stats_clone = lock.user_stats.clone();
+                // capture target before moving game back into the map
+                let revealed_target = game.target;
+                let play_again =
+                    keyboards::play_again_keyboard(&messages.button_play_again, user_id, game.id);
+                // reinsert game (with attempts_left == 0)
+                lock.by_user.insert(key, game);
+                drop(lock);
+
+                // Perform disk I/O operations without holding lock
+                let data_path = Path::new("data").join("user_start_attempts.json");
+                save_user_start_attempts(&data_path, &starts_clone);
+                let miss_path = Path::new("data").join("user_miss_streaks.json");
+                save_user_miss_streaks(&miss_path, &misses_clone);
+                let win_path = Path::new("data").join("user_win_streaks.json");
+                save_user_win_streaks(&win_path, &wins_clone);
+                let stats_path = Path::new("data").join("user_stats.json");
+                rating::save_user_stats(&stats_path, &stats_clone);
+
+                let remaining_before_reset = if streak >= rules.restart_threshold {
+                    0
+                } else {
+                    rules.restart_threshold - streak
+                };
+                let mut reply = messages.format(
+                    &messages.revealed,
+                    &[
+                        ("target", &messages.number(revealed_target)),
+                        ("number_attempts", &messages.number(remaining_before_reset)),
+                    ],
+                );
+                reply.push('\n');
+                reply.push_str(&format_rating(messages, &game_rating));
+                bot.send_message(chat_id, reply)
+                    .reply_markup(play_again)
+                    .await?;
+            } else if guess < game.target {
+                let reply = messages.format(
+                    &messages.too_low,
+                    &[("attempts", &messages.number(game.attempts_left))],
+                );
+                // reinsert game
+                lock.by_user.insert(key, game);
+                drop(lock); // Release lock before async operation
+                bot.send_message(chat_id, reply).await?;
+            } else {
+                let reply = messages.format(
+                    &messages.too_high,
+                    &[("attempts", &messages.number(game.attempts_left))],
+                );
+                // reinsert game
+                lock.by_user.insert(key, game);
+                drop(lock); // Release lock before async operation
+                bot.send_message(chat_id, reply).await?;
+            }
+        }
+    } else {
+        bot.send_message(chat_id, messages.not_started_prompt.clone())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Handle an incoming message, updating state as needed and sending replies.
+async fn handle_message(
+    bot: &Bot,
+    msg: &Message,
+    state: SharedState,
+    config: SharedConfig,
+    guards: SharedGuards,
+) -> Result<()> {
+    // banned users are ignored entirely, before any state lock
+    if let Some(user) = msg.from.as_ref()
+        && guards.is_banned(msg.chat.id.0, user.id.0)
+    {
+        return Ok(());
+    }
+    // floods are dropped here, before any state lock
+    if let Verdict::Drop { limited, notify } =
+        guards.rate_check(msg.chat.id.0, msg.from.as_ref().map(|u| u.id.0))
+    {
+        tracing::debug!(
+            "dropped a message in chat {} ({:?} rate limit)",
+            msg.chat.id.0,
+            limited
+        );
+        if notify {
+            let lang = effective_lang(
+                &state,
+                &config,
+                msg.chat.id.0,
+                msg.from.as_ref().map(|u| u.id.0),
+                msg.from.as_ref().and_then(|u| u.language_code.as_deref()),
+            )
+            .await;
+            let messages = &messages_for(&config, &lang);
+            bot.send_message(msg.chat.id, throttle_notice(messages, limited).clone())
+                .await?;
+        }
+        return Ok(());
+    }
+    let lang = effective_lang(
+        &state,
+        &config,
+        msg.chat.id.0,
+        msg.from.as_ref().map(|u| u.id.0),
+        msg.from.as_ref().and_then(|u| u.language_code.as_deref()),
+    )
+    .await;
+    let messages = &messages_for(&config, &lang);
+
+    // chats outside of the operating mode get a refusal to their commands;
+    // groups are left instead when LEAVE_REFUSED_CHATS is set
+    if let Err(refusal) = guards.access_check(
+        &config,
+        msg.chat.id.0,
+        msg.chat.is_private(),
+        msg.from.as_ref().map(|u| u.id.0),
+    ) {
+        let leave = config.leave_refused_chats && !msg.chat.is_private();
+        if leave || msg.text().is_some_and(|t| t.starts_with('/')) {
+            bot.send_message(msg.chat.id, refusal_text(messages, refusal).clone())
+                .await?;
+        }
+        if leave {
+            tracing::info!("leaving chat {} ({:?})", msg.chat.id.0, refusal);
+            bot.leave_chat(msg.chat.id).await?;
+        }
+        return Ok(());
+    }
+
+    if let Some(text) = msg.text() {
+        let text = text.trim();
+        if is_command(text, "/reset_starts") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                    .await?;
+                return Ok(());
+            };
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let replied = msg
+                .reply_to_message()
+                .and_then(|m| m.from.as_ref())
+                .map(|u| u.id.0);
+            let chat_id = msg.chat.id.0;
+            // /reset_starts <user_id>, /reset_starts chat, /reset_starts
+            // [conferma]; in reply to a message it targets that user
+            let (scope, permission) = match (parts.get(1), replied) {
+                (None, Some(user_id)) => (
+                    Some(ResetScope::User { chat_id, user_id }),
+                    Permission::ResetUser,
+                ),
+                (None, None) => (None, Permission::ResetStarts),
+                (Some(p), _) if p.eq_ignore_ascii_case("conferma") => {
+                    (Some(ResetScope::All), Permission::ResetStarts)
+                }
+                (Some(p), _) if p.eq_ignore_ascii_case("chat") => {
+                    (Some(ResetScope::Chat(chat_id)), Permission::ResetChat)
+                }
+                (Some(p), _) => match p.parse::<u64>() {
+                    Ok(user_id) => (
+                        Some(ResetScope::User { chat_id, user_id }),
+                        Permission::ResetUser,
+                    ),
+                    Err(_) => {
+                        bot.send_message(msg.chat.id, messages.reset_usage.clone())
                             .await?;
                         return Ok(());
                     }
+                },
+            };
+            if parts.len() > 2 {
+                bot.send_message(msg.chat.id, messages.reset_usage.clone())
+                    .await?;
+                return Ok(());
+            }
+            if !sender_may(bot, &state, &config, msg, permission).await {
+                audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let now = now_unix();
+            let mut lock = state.write().await;
+            let Some(scope) = scope else {
+                // the global wipe waits for `/reset_starts conferma`
+                lock.pending_resets.insert(user.id.0, now);
+                audit(&mut lock, msg, AuditOutcome::Pending);
+                drop(lock);
+                bot.send_message(msg.chat.id, messages.reset_all_confirm.clone())
+                    .await?;
+                return Ok(());
+            };
+            if scope == ResetScope::All {
+                let confirmed = lock
+                    .pending_resets
+                    .remove(&user.id.0)
+                    .is_some_and(|ts| now.saturating_sub(ts) <= RESET_CONFIRM_WINDOW_SECS);
+                if !confirmed {
+                    audit(&mut lock, msg, AuditOutcome::Failed);
+                    drop(lock);
+                    bot.send_message(msg.chat.id, messages.reset_all_no_pending.clone())
+                        .await?;
+                    return Ok(());
                 }
             }
-            if parts.len() == 3 && parts[1].eq_ignore_ascii_case("chat") {
-                if let Some(new_lang) = crate::parse_lang(parts[2]) {
-                    lock.chat_langs.insert(msg.chat.id.0, new_lang);
-                    bot.send_message(msg.chat.id, messages.lang_set_chat.clone())
-                        .await?;
-                    return Ok(());
-                }
+            let count = resets::reset_progress(&mut lock, scope);
+            save_progress(&lock);
+            let outcome = if count > 0 {
+                AuditOutcome::Done
+            } else {
+                AuditOutcome::Unchanged
+            };
+            audit(&mut lock, msg, outcome);
+            drop(lock);
+            tracing::info!(
+                "progression reset ({:?}) by user {}: {} entries",
+                scope,
+                user.id.0,
+                count
+            );
+            let reply = match scope {
+                ResetScope::User { user_id, .. } => {
+                    messages.format(&messages.reset_user_done, &[("user", &user_id.to_string())])
+                }
+                ResetScope::Chat(_) => messages.format(
+                    &messages.reset_chat_done,
+                    &[("count", &messages.number(count))],
+                ),
+                ResetScope::All => messages.reset_starts_ok.clone(),
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if text.eq_ignore_ascii_case("/reset_me") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                    .await?;
+                return Ok(());
+            };
+            let scope = ResetScope::User {
+                chat_id: msg.chat.id.0,
+                user_id: user.id.0,
+            };
+            let mut lock = state.write().await;
+            resets::reset_progress(&mut lock, scope);
+            save_progress(&lock);
+            let attempts = chat_rules(&lock, &config, msg.chat.id.0).attempts;
+            drop(lock);
+            let reply = messages.format(
+                &messages.reset_me_done,
+                &[("attempts", &messages.number(attempts))],
+            );
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/modalita") {
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            // /modalita <mode>, or predefinita to follow OPERATING_MODE again
+            let change = match parts.as_slice() {
+                [_] => None,
+                [_, m] if m.eq_ignore_ascii_case("predefinita") => Some(Some(None)),
+                [_, m] => Some(access::parse_operating_mode(m).map(Some)),
+                _ => Some(None),
+            };
+            if !sender_may(bot, &state, &config, msg, Permission::ManageAccess).await {
+                if change.is_some() {
+                    audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                }
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let Some(change) = change else {
+                let list = guards.access_list();
+                let reply = messages.format(
+                    &messages.access_mode_status,
+                    &[
+                        ("mode", list.mode(config.operating_mode).name()),
+                        ("configured", if list.mode.is_none() { "yes" } else { "no" }),
+                    ],
+                );
+                bot.send_message(msg.chat.id, reply).await?;
+                return Ok(());
+            };
+            let Some(mode) = change else {
+                bot.send_message(msg.chat.id, messages.access_mode_usage.clone())
+                    .await?;
+                return Ok(());
+            };
+            let changed = guards.update_access(|access| {
+                let changed = access.mode != mode;
+                access.mode = mode;
+                changed
+            });
+            let outcome = if changed {
+                AuditOutcome::Done
+            } else {
+                AuditOutcome::Unchanged
+            };
+            audit(&mut *state.write().await, msg, outcome);
+            let mode = mode.unwrap_or(config.operating_mode);
+            tracing::info!("operating mode set to {}", mode.name());
+            let reply = messages.format(
+                &messages.access_mode_set,
+                &[
+                    ("mode", mode.name()),
+                    ("changed", if changed { "yes" } else { "no" }),
+                ],
+            );
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/chat_consentite") {
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            // /chat_consentite aggiungi|rimuovi [chat_id]; the current chat
+            // without an id
+            let change = match parts.as_slice() {
+                [_] => None,
+                [_, action] => Some((action.to_lowercase(), Some(msg.chat.id.0))),
+                [_, action, id] => Some((action.to_lowercase(), id.parse::<i64>().ok())),
+                _ => Some((String::new(), None)),
+            };
+            if !sender_may(bot, &state, &config, msg, Permission::ManageAccess).await {
+                if change.is_some() {
+                    audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                }
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let Some((action, chat)) = change else {
+                let list = guards.access_list();
+                let reply = messages.format(
+                    &messages.access_list,
+                    &[
+                        ("mode", list.mode(config.operating_mode).name()),
+                        ("chats", &chat_list(&list.chats, messages)),
+                        ("configured", &chat_list(&config.allowed_chats, messages)),
+                    ],
+                );
+                bot.send_message(msg.chat.id, reply).await?;
+                return Ok(());
+            };
+            let (Some(chat), true) = (chat, matches!(action.as_str(), "aggiungi" | "rimuovi"))
+            else {
+                bot.send_message(msg.chat.id, messages.access_usage.clone())
+                    .await?;
+                return Ok(());
+            };
+            if action == "rimuovi" && config.allowed_chats.contains(&chat) {
+                audit(&mut *state.write().await, msg, AuditOutcome::Unchanged);
+                let reply = messages.format(
+                    &messages.access_chat_configured,
+                    &[("chat", &chat.to_string())],
+                );
+                bot.send_message(msg.chat.id, reply).await?;
+                return Ok(());
+            }
+            let changed = guards.update_access(|access| {
+                if action == "aggiungi" {
+                    access.chats.insert(chat)
+                } else {
+                    access.chats.remove(&chat)
+                }
+            });
+            let outcome = if changed {
+                AuditOutcome::Done
+            } else {
+                AuditOutcome::Unchanged
+            };
+            audit(&mut *state.write().await, msg, outcome);
+            let reply = messages.format(
+                &messages.access_chat_changed,
+                &[
+                    ("chat", &chat.to_string()),
+                    (
+                        "action",
+                        if action == "aggiungi" {
+                            "add"
+                        } else {
+                            "remove"
+                        },
+                    ),
+                    ("changed", if changed { "yes" } else { "no" }),
+                ],
+            );
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/manutenzione") {
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let on = match parts.get(1).map(|p| p.to_lowercase()).as_deref() {
+                Some("on") if parts.len() == 2 => Some(true),
+                Some("off") if parts.len() == 2 => Some(false),
+                _ => None,
+            };
+            if !sender_may(bot, &state, &config, msg, Permission::Maintenance).await {
+                if on.is_some() {
+                    audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                }
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let Some(on) = on else {
+                let reply = match guards.maintenance_since() {
+                    Some(since) => messages.format(
+                        &messages.maintenance_status_on,
+                        &[("since", &audit::format_timestamp(since))],
+                    ),
+                    None => messages.maintenance_status_off.clone(),
+                };
+                bot.send_message(msg.chat.id, reply).await?;
+                return Ok(());
+            };
+            let changed = guards.set_maintenance(on);
+            let outcome = if changed {
+                AuditOutcome::Done
+            } else {
+                AuditOutcome::Unchanged
+            };
+            audit(&mut *state.write().await, msg, outcome);
+            tracing::info!("maintenance mode {}", if on { "on" } else { "off" });
+            let reply = if on {
+                messages.maintenance_on.clone()
+            } else {
+                messages.maintenance_off.clone()
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/annuncio") {
+            if !sender_may(bot, &state, &config, msg, Permission::Broadcast).await {
+                audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            // the text keeps its line breaks: split off the command only
+            let body = text
+                .split_once(char::is_whitespace)
+                .map(|(_, rest)| rest)
+                .unwrap_or("");
+            let Some(announcement) = Announcement::parse(body) else {
+                bot.send_message(msg.chat.id, messages.broadcast_usage.clone())
+                    .await?;
+                return Ok(());
+            };
+            let chats = broadcast::known_chats(&*state.read().await);
+            audit(&mut *state.write().await, msg, AuditOutcome::Done);
+            let reply = messages.format(
+                &messages.broadcast_started,
+                &[("count", &messages.number(chats.len()))],
+            );
+            bot.send_message(msg.chat.id, reply).await?;
+            tokio::spawn(run_broadcast(
+                bot.clone(),
+                state.clone(),
+                config.clone(),
+                announcement,
+                chats,
+                msg.chat.id,
+                lang.clone(),
+            ));
+            return Ok(());
+        }
+        if is_command(text, "/imposta") {
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let chat_id = msg.chat.id.0;
+            if parts.len() == 1 {
+                bot.send_message(msg.chat.id, messages.settings_usage.clone())
+                    .await?;
+                return Ok(());
+            }
+            if !sender_may(bot, &state, &config, msg, Permission::ConfigureChat).await {
+                audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let mut lock = state.write().await;
+            let current = lock
+                .chat_settings
+                .get(&chat_id)
+                .cloned()
+                .unwrap_or_default();
+            // `/imposta predefiniti` goes back to the configuration values
+            let updated = if parts.len() == 2 && parts[1].eq_ignore_ascii_case("predefiniti") {
+                let mut cleared = current.clone();
+                cleared.clear_game_overrides();
+                Some(cleared)
+            } else {
+                chat_settings::apply_rule_args(&current, &parts[1..])
+            };
+            let Some(updated) = updated else {
+                drop(lock);
+                bot.send_message(msg.chat.id, messages.settings_usage.clone())
+                    .await?;
+                return Ok(());
+            };
+            let rules = updated.game_rules(config.game_rules());
+            if let Err(e) = rules.validate() {
+                audit(&mut lock, msg, AuditOutcome::Failed);
+                drop(lock);
+                bot.send_message(msg.chat.id, rules_error(messages, e))
+                    .await?;
+                return Ok(());
+            }
+            let outcome = if updated == current {
+                AuditOutcome::Unchanged
+            } else {
+                AuditOutcome::Done
+            };
+            let reset = !updated.has_game_overrides();
+            if updated.is_empty() {
+                lock.chat_settings.remove(&chat_id);
+            } else {
+                lock.chat_settings.insert(chat_id, updated);
+            }
+            let settings_path = Path::new("data").join("chat_settings.json");
+            chat_settings::save_chat_settings(&settings_path, &lock.chat_settings);
+            audit(&mut lock, msg, outcome);
+            drop(lock);
+            let reply = if reset {
+                messages.settings_reset.clone()
+            } else {
+                messages.format(
+                    &messages.settings_updated,
+                    &[
+                        ("min", &messages.number(rules.min)),
+                        ("max", &messages.number(rules.max)),
+                        ("attempts", &messages.number(rules.attempts)),
+                        ("threshold", &messages.number(rules.restart_threshold)),
+                    ],
+                )
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/bannati") {
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let (scope, permission) = match parts.get(1) {
+                Some(p) if p.eq_ignore_ascii_case("globale") => {
+                    (BanScope::Global, Permission::BanGlobally)
+                }
+                _ => (BanScope::Chat(msg.chat.id.0), Permission::BanInChat),
+            };
+            if !sender_may(bot, &state, &config, msg, permission).await {
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let reply = {
+                let bans = guards
+                    .bans
+                    .read()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                bans_listing(messages, &bans, scope, now_unix())
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/ban") || is_command(text, "/unban") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                    .await?;
+                return Ok(());
+            };
+            let lifting = is_command(text, "/unban");
+            // /ban [<user_id>] [globale] [30m|12h|7d|2w] [motivo...] and
+            // /unban [<user_id>] [globale]; without the id in reply to a
+            // message of the target user
+            let mut args = text.split_whitespace().skip(1).peekable();
+            let target = match args.peek().and_then(|a| a.parse::<u64>().ok()) {
+                Some(id) => {
+                    args.next();
+                    Some(id)
+                }
+                None => msg
+                    .reply_to_message()
+                    .and_then(|m| m.from.as_ref())
+                    .map(|u| u.id.0),
+            };
+            let global = args
+                .next_if(|a| a.eq_ignore_ascii_case("globale"))
+                .is_some();
+            let duration = if lifting {
+                None
+            } else {
+                args.next_if(|a| bans::parse_ban_duration(a).is_some())
+                    .and_then(bans::parse_ban_duration)
+            };
+            let reason = args.collect::<Vec<_>>().join(" ");
+            let Some(target) = target.filter(|_| !lifting || reason.is_empty()) else {
+                bot.send_message(msg.chat.id, messages.ban_usage.clone())
+                    .await?;
+                return Ok(());
+            };
+            let (scope, permission) = if global {
+                (BanScope::Global, Permission::BanGlobally)
+            } else {
+                (BanScope::Chat(msg.chat.id.0), Permission::BanInChat)
+            };
+            if !sender_may(bot, &state, &config, msg, permission).await {
+                audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let target_s = target.to_string();
+            // nobody can ban a user with a role as high as their own
+            if !lifting {
+                let own = user_role(bot, &state, &config, &msg.chat, user.id.0).await;
+                let theirs = user_role(bot, &state, &config, &msg.chat, target).await;
+                if theirs >= own {
+                    audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                    let reply = messages.format(&messages.ban_protected, &[("user", &target_s)]);
+                    bot.send_message(msg.chat.id, reply).await?;
+                    return Ok(());
+                }
+            }
+            let now = now_unix();
+            let reply = if lifting {
+                let lifted = guards.update_bans(|bans| bans.unban(scope, target));
+                let outcome = if lifted {
+                    AuditOutcome::Done
+                } else {
+                    AuditOutcome::Unchanged
+                };
+                audit(&mut *state.write().await, msg, outcome);
+                let template = if lifted {
+                    &messages.unban_done
+                } else {
+                    &messages.unban_not_banned
+                };
+                messages.format(
+                    template,
+                    &[("user", &target_s), ("scope", ban_scope_key(scope))],
+                )
+            } else {
+                let ban = Ban {
+                    by: user.id.0,
+                    at: now,
+                    until: duration.map(|d| now.saturating_add(d)),
+                    reason: (!reason.is_empty()).then_some(reason),
+                };
+                let until = ban_expiry(&ban);
+                guards.update_bans(|bans| bans.ban(scope, target, ban));
+                audit(&mut *state.write().await, msg, AuditOutcome::Done);
+                tracing::info!("user {} banned user {} ({:?})", user.id.0, target, scope);
+                messages.format(
+                    &messages.ban_done,
+                    &[
+                        ("user", &target_s),
+                        ("scope", ban_scope_key(scope)),
+                        ("until", &until),
+                    ],
+                )
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if text.eq_ignore_ascii_case("/status") {
+            if !sender_may(bot, &state, &config, msg, Permission::ViewStatus).await {
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let reply = status_report(&state, &config, &guards, messages).await;
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/audit") {
+            if !sender_may(bot, &state, &config, msg, Permission::ViewAudit).await {
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let page = match parts.as_slice() {
+                [_] => Some(1),
+                [_, p] => p.parse::<usize>().ok(),
+                _ => None,
+            };
+            let reply = {
+                let lock = state.read().await;
+                let log = &lock.audit_log;
+                let pages = audit::page_count(log.len(), AUDIT_PAGE_SIZE);
+                match page.and_then(|p| audit::audit_page(log, p, AUDIT_PAGE_SIZE)) {
+                    Some(entries) if entries.is_empty() => messages.audit_empty.clone(),
+                    Some(entries) => {
+                        let mut reply = messages.format(
+                            &messages.audit_header,
+                            &[
+                                ("page", &messages.number(page.unwrap_or(1))),
+                                ("pages", &messages.number(pages)),
+                            ],
+                        );
+                        for e in entries {
+                            let actor = e
+                                .actor
+                                .map(|a| a.to_string())
+                                .unwrap_or_else(|| messages.audit_anonymous.clone());
+                            let command = if e.args.is_empty() {
+                                e.command.clone()
+                            } else {
+                                format!("{} {}", e.command, e.args)
+                            };
+                            reply.push('\n');
+                            reply.push_str(&messages.format(
+                                &messages.audit_entry,
+                                &[
+                                    ("time", &audit::format_timestamp(e.timestamp)),
+                                    ("chat", &e.chat_id.to_string()),
+                                    ("actor", &actor),
+                                    ("command", &command),
+                                    ("outcome", audit_outcome_name(messages, e.outcome)),
+                                ],
+                            ));
+                        }
+                        reply
+                    }
+                    None => messages
+                        .format(&messages.audit_usage, &[("pages", &messages.number(pages))]),
+                }
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if text.eq_ignore_ascii_case("/reload_messages") {
+            if !sender_may(bot, &state, &config, msg, Permission::ReloadMessages).await {
+                audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let reply = match catalog::reload_messages(&config.messages, MESSAGES_DIR, &config.lang)
+            {
+                Ok(outcome) => {
+                    audit(&mut *state.write().await, msg, AuditOutcome::Done);
+                    tracing::info!("messages reloaded: {}", outcome.languages.join(", "));
+                    for (tag, keys) in &outcome.missing_keys {
+                        tracing::warn!(
+                            "{}/{}.json lacks {} key(s), using fallbacks: {}",
+                            MESSAGES_DIR,
+                            tag,
+                            keys.len(),
+                            keys.join(", ")
+                        );
+                    }
+                    messages.format(
+                        &messages.messages_reloaded,
+                        &[
+                            ("count", &messages.number(outcome.languages.len())),
+                            ("languages", &outcome.languages.join(", ")),
+                        ],
+                    )
+                }
+                Err(problems) => {
+                    audit(&mut *state.write().await, msg, AuditOutcome::Failed);
+                    tracing::warn!("messages not reloaded: {}", problems.join("; "));
+                    let mut shown: Vec<String> = problems
+                        .iter()
+                        .take(RELOAD_PROBLEMS_SHOWN)
+                        .map(|p| format!("• {}", p))
+                        .collect();
+                    if problems.len() > RELOAD_PROBLEMS_SHOWN {
+                        shown.push(format!("• … (+{})", problems.len() - RELOAD_PROBLEMS_SHOWN));
+                    }
+                    messages.format(
+                        &messages.messages_reload_failed,
+                        &[("problems", &shown.join("\n"))],
+                    )
+                }
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if is_command(text, "/ruoli") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                    .await?;
+                return Ok(());
+            };
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            if parts.len() == 1 {
+                let role = user_role(bot, &state, &config, &msg.chat, user.id.0).await;
+                let reply = {
+                    let lock = state.read().await;
+                    roles_listing(
+                        messages,
+                        config.bot_owner_id,
+                        &lock.role_grants,
+                        msg.chat.id.0,
+                        role,
+                    )
+                };
+                bot.send_message(msg.chat.id, reply).await?;
+                return Ok(());
+            }
+            // /ruoli aggiungi|rimuovi <user_id> <ruolo>, or the same without
+            // the id in reply to a message of the target user
+            let replied = msg
+                .reply_to_message()
+                .and_then(|m| m.from.as_ref())
+                .map(|u| u.id.0);
+            let (target, role) = match parts.as_slice() {
+                [_, _, id, role] => (id.parse::<u64>().ok(), roles::parse_grantable_role(role)),
+                [_, _, role] => (replied, roles::parse_grantable_role(role)),
+                _ => (None, None),
+            };
+            let action = parts[1].to_lowercase();
+            let (Some(target), Some(role), true) = (
+                target,
+                role,
+                matches!(action.as_str(), "aggiungi" | "rimuovi"),
+            ) else {
+                bot.send_message(msg.chat.id, messages.roles_usage.clone())
+                    .await?;
+                return Ok(());
+            };
+            if !sender_may(bot, &state, &config, msg, Permission::to_manage(role)).await {
+                audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let (changed, grants) = {
+                let mut lock = state.write().await;
+                let changed = if action == "aggiungi" {
+                    lock.role_grants.grant(msg.chat.id.0, target, role)
+                } else {
+                    lock.role_grants.revoke(msg.chat.id.0, target, role)
+                };
+                let outcome = if changed {
+                    AuditOutcome::Done
+                } else {
+                    AuditOutcome::Unchanged
+                };
+                audit(&mut lock, msg, outcome);
+                (changed, lock.role_grants.clone())
+            };
+            let target_s = target.to_string();
+            let reply = if !changed {
+                messages.format(&messages.role_unchanged, &[("user", &target_s)])
+            } else {
+                roles::save_role_grants(&Path::new("data").join("roles.json"), &grants);
+                tracing::info!(
+                    "user {} {} role {:?} of user {} in chat {}",
+                    user.id.0,
+                    if action == "aggiungi" {
+                        "granted"
+                    } else {
+                        "revoked"
+                    },
+                    role,
+                    target,
+                    msg.chat.id.0
+                );
+                let template = if action == "aggiungi" {
+                    &messages.role_granted
+                } else {
+                    &messages.role_revoked
+                };
+                messages.format(
+                    template,
+                    &[
+                        ("user", &target_s),
+                        ("role", role_name(messages, Some(role))),
+                    ],
+                )
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+        if text.eq_ignore_ascii_case("/miei_dati") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                    .await?;
+                return Ok(());
+            };
+            let export = {
+                let lock = state.read().await;
+                let bans = guards
+                    .bans
+                    .read()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                privacy::export_user_data(&lock, &bans, user.id.0)
+            };
+            let body = serde_json::to_vec_pretty(&export)?;
+            let file = InputFile::memory(body).file_name(format!("data_{}.json", user.id.0));
+            // the export is personal: always deliver it in the private chat
+            let private_chat = ChatId::from(user.id);
+            let sent = bot
+                .send_document(private_chat, file)
+                .caption(messages.data_export_caption.clone())
+                .await;
+            if msg.chat.is_private() {
+                sent?;
+            } else if sent.is_ok() {
+                bot.send_message(msg.chat.id, messages.data_export_sent.clone())
+                    .await?;
+            } else {
+                bot.send_message(msg.chat.id, messages.data_export_private_failed.clone())
+                    .await?;
+            }
+            return Ok(());
+        }
+
+        if is_command(text, "/cancella_dati") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                    .await?;
+                return Ok(());
+            };
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let confirm = parts
+                .last()
+                .is_some_and(|p| p.eq_ignore_ascii_case("conferma"));
+            // an explicit user id targets someone else's data (admins only)
+            let target = match parts.get(1) {
+                Some(p) if !p.eq_ignore_ascii_case("conferma") => match p.parse::<u64>() {
+                    Ok(id) => Some(id),
+                    Err(_) => {
+                        bot.send_message(msg.chat.id, messages.data_erase_usage.clone())
+                            .await?;
+                        return Ok(());
+                    }
+                },
+                _ => None,
+            };
+            if target.is_some_and(|t| t != user.id.0)
+                && !sender_may(bot, &state, &config, msg, Permission::EraseOthersData).await
+            {
+                audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                bot.send_message(msg.chat.id, messages.not_authorized.clone())
+                    .await?;
+                return Ok(());
+            }
+            let target_id = target.unwrap_or(user.id.0);
+            let now = now_unix();
+            let mut lock = state.write().await;
+            // erasing one's own data is not an admin action
+            let audited = target_id != user.id.0;
+            if !confirm {
+                lock.pending_erasures.insert(user.id.0, (target_id, now));
+                if audited {
+                    audit(&mut lock, msg, AuditOutcome::Pending);
+                }
+                drop(lock);
+                let reply = if target_id == user.id.0 {
+                    messages.data_erase_confirm.clone()
+                } else {
+                    messages.format(
+                        &messages.data_erase_admin_confirm,
+                        &[("user", &target_id.to_string())],
+                    )
+                };
+                bot.send_message(msg.chat.id, reply).await?;
+                return Ok(());
+            }
+            let pending = lock.pending_erasures.remove(&user.id.0);
+            let confirmed = pending.is_some_and(|(t, ts)| {
+                t == target_id && now.saturating_sub(ts) <= ERASE_CONFIRM_WINDOW_SECS
+            });
+            if !confirmed {
+                if audited {
+                    audit(&mut lock, msg, AuditOutcome::Failed);
+                }
+                drop(lock);
+                bot.send_message(msg.chat.id, messages.data_erase_no_pending.clone())
+                    .await?;
+                return Ok(());
+            }
+            let entries = privacy::erase_user_data(&mut lock, target_id);
+            save_all_data(&lock);
+            if audited {
+                audit(&mut lock, msg, AuditOutcome::Done);
+            }
+            drop(lock);
+            tracing::info!(
+                "erased data of user {} on request of {} ({} entries)",
+                target_id,
+                user.id.0,
+                entries
+            );
+            let reply = if target_id == user.id.0 {
+                messages.format(
+                    &messages.data_erase_done,
+                    &[("entries", &messages.number(entries))],
+                )
+            } else {
+                messages.format(
+                    &messages.data_erase_admin_done,
+                    &[
+                        ("user", &target_id.to_string()),
+                        ("entries", &messages.number(entries)),
+                    ],
+                )
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+
+        if text.eq_ignore_ascii_case("/ping") {
+            bot.send_message(msg.chat.id, messages.pong.clone()).await?;
+            return Ok(());
+        }
+
+        // games are closed during maintenance; running games are kept
+        let game_command =
+            text.eq_ignore_ascii_case("/gioco") || text.eq_ignore_ascii_case("/tastierino");
+        if guards.maintenance_since().is_some()
+            && (game_command || numbers::parse_guess(text, &messages.tag).is_some())
+        {
+            bot.send_message(msg.chat.id, messages.maintenance_active.clone())
+                .await?;
+            return Ok(());
+        }
+
+        if text.eq_ignore_ascii_case("/gioco") {
+            let user_id = match msg.from.as_ref().map(|u| u.id.0) {
+                Some(id) => id,
+                None => {
+                    bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                        .await?;
+                    return Ok(());
+                }
+            };
+            start_game(bot, msg.chat.id, user_id, &state, &config, messages).await?;
+            return Ok(());
+        }
+
+        if text.eq_ignore_ascii_case("/tastierino") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_guess.clone())
+                    .await?;
+                return Ok(());
+            };
+            let key = (msg.chat.id.0, user.id.0);
+            let game_id = {
+                let mut lock = state.write().await;
+                lock.keypad_input.remove(&key);
+                lock.by_user.get(&key).map(|g| g.id)
+            };
+            match game_id {
+                Some(id) => {
+                    bot.send_message(msg.chat.id, messages.keypad_prompt_empty.clone())
+                        .reply_markup(keyboards::keypad_keyboard(user.id.0, id))
+                        .await?;
+                }
+                None => {
+                    bot.send_message(msg.chat.id, messages.not_started_prompt.clone())
+                        .await?;
+                }
+            }
+            return Ok(());
+        }
+
+        if text.to_lowercase().starts_with("/lang") {
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let mut lock = state.write().await;
+            if parts.len() == 1 {
+                let (reply, languages) = language_listing(messages, &config.messages.snapshot());
+                bot.send_message(msg.chat.id, reply)
+                    .reply_markup(keyboards::language_keyboard(&languages))
+                    .await?;
+                return Ok(());
+            }
+            if parts.len() == 2
+                && let Some(new_lang) = requested_lang(&config, parts[1])
+            {
+                if let Some(user) = msg.from.as_ref() {
+                    let key = (msg.chat.id.0, user.id.0);
+                    lock.user_langs.insert(key, new_lang);
+                    bot.send_message(msg.chat.id, messages.lang_set_user.clone())
+                        .await?;
+                    return Ok(());
+                } else {
+                    bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                        .await?;
+                    return Ok(());
+                }
+            }
+            if parts.len() == 3
+                && parts[1].eq_ignore_ascii_case("chat")
+                && let Some(new_lang) = requested_lang(&config, parts[2])
+            {
+                drop(lock);
+                if !sender_may(bot, &state, &config, msg, Permission::SetChatLang).await {
+                    audit(&mut *state.write().await, msg, AuditOutcome::Denied);
+                    bot.send_message(msg.chat.id, messages.lang_chat_admin_only.clone())
+                        .await?;
+                    return Ok(());
+                }
+                let mut lock = state.write().await;
+                lock.chat_settings.entry(msg.chat.id.0).or_default().lang = Some(new_lang);
+                let settings_path = Path::new("data").join("chat_settings.json");
+                chat_settings::save_chat_settings(&settings_path, &lock.chat_settings);
+                audit(&mut lock, msg, AuditOutcome::Done);
+                drop(lock);
+                bot.send_message(msg.chat.id, messages.lang_set_chat.clone())
+                    .await?;
+                return Ok(());
+            }
+            let reply = messages.format(
+                &messages.lang_invalid,
+                &[("lang_commands", &lang_commands(&config))],
+            );
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+
+        if text.eq_ignore_ascii_case("/statistiche") {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_start.clone())
+                    .await?;
+                return Ok(());
+            };
+            let composite = format!("{}:{}", msg.chat.id.0, user.id.0);
+            let stats = state.read().await.user_stats.get(&composite).cloned();
+            let reply = match stats {
+                Some(st) if st.games > 0 => messages.format(
+                    &messages.stats,
+                    &[
+                        ("games", &messages.number(st.games)),
+                        ("wins", &messages.number(st.wins)),
+                        ("losses", &messages.number(st.losses)),
+                        ("efficiency", &messages.number(st.average_efficiency())),
+                        ("wasted", &messages.number(st.wasted_guesses)),
+                        ("guesses", &messages.number(st.guesses)),
+                    ],
+                ),
+                _ => messages.stats_empty.clone(),
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+            return Ok(());
+        }
+
+        if text.eq_ignore_ascii_case("/stagione") {
+            if config.season_period.is_none() {
+                bot.send_message(msg.chat.id, messages.seasons_disabled.clone())
+                    .await?;
+                return Ok(());
             }
-            bot.send_message(msg.chat.id, messages.lang_invalid.clone())
-                .await?;
+            let reply = {
+                let lock = state.read().await;
+                season_report(&lock.season, &lock.season_archive, msg.chat.id.0, messages)
+            };
+            bot.send_message(msg.chat.id, reply).await?;
             return Ok(());
         }
 
         if text.eq_ignore_ascii_case("/config") {
             // Extract data with minimal lock duration
-            let (attempts_str, next_attempts_str, number_attempts_s) =
-                if let Some(user) = msg.from.as_ref() {
-                    let key = (msg.chat.id.0, user.id.0);
-                    let composite = format!("{}:{}", msg.chat.id.0, user.id.0);
-
-                    // Acquire lock only to read necessary data, then release immediately
-                    let (game_state, streak) = {
-                        let lock = state.read().await;
-                        (
-                            lock.by_user
-                                .get(&key)
-                                .map(|g| (g.attempts_left, g.start_attempts)),
-                            lock.user_miss_streaks.get(&composite).copied().unwrap_or(0),
-                        )
-                    }; // Lock is automatically released here
-
-                    let (attempts, next_attempts) =
-                        game_state.unwrap_or((config.attempts, config.attempts));
-
-                    let remaining = if streak >= config.restart_threshold {
-                        0
-                    } else {
-                        config.restart_threshold - streak
-                    };
-
-                    (
-                        attempts.to_string(),
-                        next_attempts.to_string(),
-                        remaining.to_string(),
-                    )
-                } else {
-                    (
-                        config.attempts.to_string(),
-                        config.attempts.to_string(),
-                        config.restart_threshold.to_string(),
-                    )
-                };
+            let (rules, overridden, game_state, streak) = {
+                let lock = state.read().await;
+                let chat_id = msg.chat.id.0;
+                let user_key = msg.from.as_ref().map(|u| (chat_id, u.id.0));
+                (
+                    chat_rules(&lock, &config, chat_id),
+                    lock.chat_settings
+                        .get(&chat_id)
+                        .is_some_and(ChatSettings::has_game_overrides),
+                    user_key.and_then(|key| {
+                        lock.by_user
+                            .get(&key)
+                            .map(|g| (g.attempts_left, g.start_attempts))
+                    }),
+                    user_key
+                        .and_then(|(c, u)| {
+                            lock.user_miss_streaks.get(&format!("{}:{}", c, u)).copied()
+                        })
+                        .unwrap_or(0),
+                )
+            }; // Lock is automatically released here
+
+            let (attempts, next_attempts) = game_state.unwrap_or((rules.attempts, rules.attempts));
+            let remaining = if streak >= rules.restart_threshold {
+                0
+            } else {
+                rules.restart_threshold - streak
+            };
 
-            let reply = format_with(
+            let mut reply = messages.format(
                 &messages.config,
                 &[
-                    ("min", &config.min.to_string()),
-                    ("max", &config.max.to_string()),
-                    ("attempts", &attempts_str),
-                    ("number_attempts", &number_attempts_s),
-                    ("next_attempts", &next_attempts_str),
+                    ("min", &messages.number(rules.min)),
+                    ("max", &messages.number(rules.max)),
+                    ("attempts", &messages.number(attempts)),
+                    ("number_attempts", &messages.number(remaining)),
+                    ("next_attempts", &messages.number(next_attempts)),
                 ],
             );
+            if overridden {
+                reply.push('\n');
+                reply.push_str(&messages.config_chat_overrides);
+            }
             bot.send_message(msg.chat.id, reply).await?;
             return Ok(());
         }
@@ -566,7 +3298,10 @@ async fn handle_message(
             let lock_read = state.read().await;
             let has_game = lock_read.by_user.contains_key(&key);
             drop(lock_read);
-            if !has_game && !text.starts_with('/') && text.parse::<i32>().is_err() {
+            if !has_game
+                && !text.starts_with('/')
+                && numbers::parse_guess(text, &messages.tag).is_none()
+            {
                 // persisted welcome: key is "chat:user" string
                 let composite = format!("{}:{}", msg.chat.id.0, user.id.0);
                 let mut lock = state.write().await;
@@ -574,166 +3309,527 @@ async fn handle_message(
                 let now = now_unix();
                 if seen_ts == 0 || now.saturating_sub(seen_ts) > config.ttl_seconds {
                     let name = user.first_name.clone();
-                    let reply = format_with(&messages.welcome_prompt, &[("name", name.as_str())]);
+                    let reply = messages.format(
+                        &messages.welcome_prompt,
+                        &[
+                            ("name", name.as_str()),
+                            ("lang_commands", &lang_commands(&config)),
+                        ],
+                    );
                     bot.send_message(msg.chat.id, reply).await?;
                     lock.seen_welcome.insert(composite.clone(), now);
                     // persist to disk; visible path used in run_bot
                     let data_path = Path::new("data").join("seen_welcome.json");
-                    let _ = save_seen_welcome(&data_path, &lock.seen_welcome);
+                    save_seen_welcome(&data_path, &lock.seen_welcome);
                     return Ok(());
                 }
             }
         }
 
-        if let Ok(guess) = text.parse::<i32>() {
-            let mut lock = state.write().await;
-            let user_id = match msg.from.as_ref().map(|u| u.id.0) {
-                Some(id) => id,
-                None => {
-                    bot.send_message(msg.chat.id, messages.cannot_guess.clone())
-                        .await?;
-                    return Ok(());
-                }
+        if let Some(guess) = numbers::parse_guess(text, &messages.tag) {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, messages.cannot_guess.clone())
+                    .await?;
+                return Ok(());
             };
-            let key = (msg.chat.id.0, user_id);
-            if let Some(mut game) = lock.by_user.remove(&key) {
-                // check if no attempts left before decrement
-                if game.attempts_left == 0 {
-                    // reinsert and notify
-                    lock.by_user.insert(key, game);
-                    bot.send_message(msg.chat.id, messages.no_attempts.clone())
-                        .await?;
-                    return Ok(());
-                }
+            process_guess(bot, msg.chat.id, user, guess, &state, &config, messages).await?;
+        }
+    }
 
-                // decrement owned game attempts
-                game.attempts_left = game.attempts_left.saturating_sub(1);
-
-                if guess == game.target {
-                    // compute attempts for the next game depending on how fast
-                    // the user succeeded. Note: `game.attempts_left` has
-                    // already been decremented above, so pass that as
-                    // `remaining_after_guess`.
-                    let next_attempts = next_attempts_after_win(
-                        game.start_attempts,
-                        game.attempts_left,
-                        config.restart_threshold,
-                    );
-                    tracing::info!(
-                        "win: chat={} user={} prev_start={} remaining_after_guess={} next= {}",
-                        msg.chat.id.0,
-                        user_id,
-                        game.start_attempts,
-                        game.attempts_left,
-                        next_attempts
-                    );
-                    // format success message with next_attempts and number_attempts placeholders
-                    // Fill placeholders correctly:
-                    // - `{next_attempts}` = computed next_attempts (e.g. GAME_ATTEMPTS - 1)
-                    // - `{number_attempts}` = restart threshold (NUMBER_ATTEMPTS)
-                    let success_msg = format_with(
-                        &messages.success_correct,
-                        &[
-                            ("next_attempts", &next_attempts.to_string()),
-                            ("number_attempts", &config.restart_threshold.to_string()),
-                        ],
-                    );
-                    bot.send_message(msg.chat.id, success_msg).await?;
+    Ok(())
+}
 
-                    // reset game to next_attempts
-                    game = GameState {
-                        target: rand_in_range(config.min, config.max),
-                        attempts_left: next_attempts,
-                        start_attempts: next_attempts,
-                    };
+/// Handle an inline keyboard button press. Every button is validated against
+/// the current state so presses on stale or foreign buttons only get a short
+/// notice instead of touching a game.
+async fn handle_callback(
+    bot: &Bot,
+    q: &CallbackQuery,
+    state: SharedState,
+    config: SharedConfig,
+    guards: SharedGuards,
+) -> Result<()> {
+    // button presses share the buckets of messages; the notice is a toast
+    let chat = q.message.as_ref().map(|m| m.chat().id.0).unwrap_or(0);
+    if guards.is_banned(chat, q.from.id.0) {
+        bot.answer_callback_query(q.id.clone()).await?;
+        return Ok(());
+    }
+    if let Verdict::Drop { limited, notify } = guards.rate_check(chat, Some(q.from.id.0)) {
+        let mut answer = bot.answer_callback_query(q.id.clone());
+        if notify {
+            let lang = effective_lang(
+                &state,
+                &config,
+                chat,
+                Some(q.from.id.0),
+                q.from.language_code.as_deref(),
+            )
+            .await;
+            let messages = &messages_for(&config, &lang);
+            answer = answer.text(throttle_notice(messages, limited).clone());
+        }
+        answer.await?;
+        return Ok(());
+    }
+    let (Some(message), Some(action)) = (
+        q.message.as_ref(),
+        q.data.as_deref().and_then(keyboards::parse_callback_data),
+    ) else {
+        bot.answer_callback_query(q.id.clone()).await?;
+        return Ok(());
+    };
+    let chat_id = message.chat().id;
+    let user = &q.from;
+    let lang = effective_lang(
+        &state,
+        &config,
+        chat_id.0,
+        Some(user.id.0),
+        user.language_code.as_deref(),
+    )
+    .await;
+    let messages = &messages_for(&config, &lang);
+    if let Err(refusal) = guards.access_check(
+        &config,
+        chat_id.0,
+        message.chat().is_private(),
+        Some(user.id.0),
+    ) {
+        bot.answer_callback_query(q.id.clone())
+            .text(refusal_text(messages, refusal).clone())
+            .await?;
+        return Ok(());
+    }
 
-                    // reset miss streak on a win and persist
-                    let composite = format!("{}:{}", msg.chat.id.0, user_id);
-                    lock.user_miss_streaks.insert(composite.clone(), 0);
-                    lock.user_start_attempts
-                        .insert(composite.clone(), next_attempts);
-                    // clone maps for persisting
-                    let starts_clone = lock.user_start_attempts.clone();
-                    let misses_clone = lock.user_miss_streaks.clone();
-                    // reinsert updated game then drop lock to persist
-                    lock.by_user.insert(key, game);
-                    drop(lock);
+    // reject buttons of someone else's game or of a game that is over
+    let bound_game = match &action {
+        CallbackAction::PlayAgain { user_id, game_id }
+        | CallbackAction::Keypad {
+            user_id, game_id, ..
+        } => Some((*user_id, *game_id)),
+        CallbackAction::SetLang { .. } => None,
+    };
+    if bound_game.is_some() && guards.maintenance_since().is_some() {
+        bot.answer_callback_query(q.id.clone())
+            .text(messages.maintenance_active.clone())
+            .await?;
+        return Ok(());
+    }
+    if let Some((owner, game_id)) = bound_game {
+        let rejection = if owner != user.id.0 {
+            Some(&messages.button_not_yours)
+        } else {
+            let lock = state.read().await;
+            let current = lock.by_user.get(&(chat_id.0, owner));
+            let valid = if matches!(action, CallbackAction::PlayAgain { .. }) {
+                current.is_some_and(|g| g.accepts_play_again(game_id))
+            } else {
+                current.is_some_and(|g| g.id == game_id)
+            };
+            (!valid).then_some(&messages.button_stale)
+        };
+        if let Some(text) = rejection {
+            bot.answer_callback_query(q.id.clone())
+                .text(text.clone())
+                .await?;
+            return Ok(());
+        }
+    }
 
-                    // Perform disk I/O operations without holding lock
-                    let data_path = Path::new("data").join("user_start_attempts.json");
-                    let _ = save_user_start_attempts(&data_path, &starts_clone);
-                    let miss_path = Path::new("data").join("user_miss_streaks.json");
-                    let _ = save_user_miss_streaks(&miss_path, &misses_clone);
-                } else {
-                    if game.attempts_left == 0 {
-                        // user ran out of attempts -> increment miss streak and persist
-                        let composite = format!("{}:{}", msg.chat.id.0, user_id);
-                        let streak =
-                            lock.user_miss_streaks.get(&composite).copied().unwrap_or(0) + 1;
-                        lock.user_miss_streaks.insert(composite.clone(), streak);
-                        if streak >= config.restart_threshold {
-                            lock.user_start_attempts
-                                .insert(composite.clone(), config.attempts);
-                            lock.user_miss_streaks.insert(composite.clone(), 0);
-                        }
-                        let starts_clone = lock.user_start_attempts.clone();
-                        let misses_clone = lock.user_miss_streaks.clone();
-                        // capture target before moving game back into the map
-                        let revealed_target = game.target;
-                        // reinsert game (with attempts_left == 0)
-                        lock.by_user.insert(key, game);
-                        drop(lock);
-
-                        // Perform disk I/O operations without holding lock
-                        let data_path = Path::new("data").join("user_start_attempts.json");
-                        let _ = save_user_start_attempts(&data_path, &starts_clone);
-                        let miss_path = Path::new("data").join("user_miss_streaks.json");
-                        let _ = save_user_miss_streaks(&miss_path, &misses_clone);
-
-                        let remaining_before_reset = if streak >= config.restart_threshold {
-                            0
-                        } else {
-                            config.restart_threshold - streak
-                        };
-                        let reply = format_with(
-                            &messages.revealed,
-                            &[
-                                ("target", &revealed_target.to_string()),
-                                ("number_attempts", &remaining_before_reset.to_string()),
-                            ],
-                        );
-                        bot.send_message(msg.chat.id, reply).await?;
-                    } else if guess < game.target {
-                        let reply = format_with(
-                            &messages.too_low,
-                            &[("attempts", &game.attempts_left.to_string())],
-                        );
-                        // reinsert game
-                        lock.by_user.insert(key, game);
-                        drop(lock); // Release lock before async operation
-                        bot.send_message(msg.chat.id, reply).await?;
-                    } else {
-                        let reply = format_with(
-                            &messages.too_high,
-                            &[("attempts", &game.attempts_left.to_string())],
-                        );
-                        // reinsert game
-                        lock.by_user.insert(key, game);
-                        drop(lock); // Release lock before async operation
-                        bot.send_message(msg.chat.id, reply).await?;
-                    }
+    match action {
+        CallbackAction::PlayAgain { .. } => {
+            bot.answer_callback_query(q.id.clone()).await?;
+            start_game(bot, chat_id, user.id.0, &state, &config, messages).await?;
+        }
+        CallbackAction::SetLang { tag } => {
+            let new_lang = requested_lang(&config, &tag);
+            let Some(new_lang) = new_lang else {
+                bot.answer_callback_query(q.id.clone())
+                    .text(messages.button_stale.clone())
+                    .await?;
+                return Ok(());
+            };
+            state
+                .write()
+                .await
+                .user_langs
+                .insert((chat_id.0, user.id.0), new_lang.clone());
+            bot.answer_callback_query(q.id.clone()).await?;
+            let new_messages = &messages_for(&config, &new_lang);
+            bot.send_message(chat_id, new_messages.lang_set_user.clone())
+                .await?;
+        }
+        CallbackAction::Keypad { game_id, key, .. } => {
+            let input_key = (chat_id.0, user.id.0);
+            if key != KeypadKey::Submit {
+                bot.answer_callback_query(q.id.clone()).await?;
+                // read and update under one guard so quick presses keep
+                // every digit
+                let (previous, input) = {
+                    let mut lock = state.write().await;
+                    let previous = lock
+                        .keypad_input
+                        .get(&input_key)
+                        .cloned()
+                        .unwrap_or_default();
+                    let input = keyboards::apply_keypad_key(&previous, key);
+                    lock.keypad_input.insert(input_key, input.clone());
+                    (previous, input)
+                };
+                if input == previous {
+                    return Ok(());
                 }
-            } else {
-                bot.send_message(msg.chat.id, messages.not_started_prompt.clone())
+                let text = if input.is_empty() {
+                    messages.keypad_prompt_empty.clone()
+                } else {
+                    messages.format(&messages.keypad_prompt, &[("input", &input)])
+                };
+                bot.edit_message_text(chat_id, message.id(), text)
+                    .reply_markup(keyboards::keypad_keyboard(user.id.0, game_id))
                     .await?;
+                return Ok(());
             }
+            let input = state
+                .write()
+                .await
+                .keypad_input
+                .remove(&input_key)
+                .unwrap_or_default();
+            let Ok(guess) = input.parse::<i32>() else {
+                bot.answer_callback_query(q.id.clone())
+                    .text(messages.keypad_prompt_empty.clone())
+                    .await?;
+                return Ok(());
+            };
+            bot.answer_callback_query(q.id.clone()).await?;
+            process_guess(bot, chat_id, user, guess, &state, &config, messages).await?;
+            // keep the keypad usable: a win starts a new game with a new id
+            let current = state
+                .read()
+                .await
+                .by_user
+                .get(&input_key)
+                .map(|g| g.id)
+                .unwrap_or(game_id);
+            bot.edit_message_text(chat_id, message.id(), messages.keypad_prompt_empty.clone())
+                .reply_markup(keyboards::keypad_keyboard(user.id.0, current))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run the bot (previously in main). Separated so binaries can call this and
+/// tests/integration can import the library.
+pub async fn run_bot() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    // what .env defines, to tell it from the process environment in /status
+    let dotenv_vars: HashMap<String, String> = dotenvy::dotenv_iter()
+        .map(|vars| vars.filter_map(|v| v.ok()).collect())
+        .unwrap_or_default();
+    dotenv().ok();
+    let mut sources = ConfigSources::new(dotenv_vars);
+    sources.var("TELOXIDE_TOKEN");
+    let bot = Bot::from_env();
+
+    // any BCP-47 tag; checked against the loaded messages below
+    let default_lang = match sources.var("DEFAULT_LANG") {
+        Some(v) => parse_lang(&v).unwrap_or_else(|| {
+            tracing::warn!("DEFAULT_LANG ({}) is not a valid language tag, using en", v);
+            sources.invalid("DEFAULT_LANG");
+            Lang::en()
+        }),
+        None => Lang::en(),
+    };
+
+    // seasons are monthly unless SEASON_PERIOD says otherwise ("off" disables them)
+    let season_period = match sources.var("SEASON_PERIOD") {
+        Some(v) if matches!(v.trim().to_lowercase().as_str(), "" | "off" | "none") => None,
+        Some(v) => match seasons::parse_season_period(&v) {
+            Some(p) => Some(p),
+            None => anyhow::bail!(
+                "Invalid configuration: SEASON_PERIOD ({}) must be weekly, monthly or off.",
+                v
+            ),
+        },
+        None => Some(SeasonPeriod::Monthly),
+    };
+
+    // LANG_ALIASES extends (or overrides) the built-in aliases
+    let mut lang_aliases = lang::default_lang_aliases();
+    if let Some(v) = sources.var("LANG_ALIASES") {
+        match lang::parse_lang_aliases(&v) {
+            Ok(extra) => lang_aliases.extend(extra),
+            Err(e) => anyhow::bail!("Invalid configuration: LANG_ALIASES: {}.", e),
+        }
+    }
+
+    // DEFAULT_LANG must resolve to a language file along its lookup chain
+    // (English is built in); the resolved tag is the fallback of every
+    // missing key, at startup as on /reload_messages
+    let file_tags = message_file_tags(MESSAGES_DIR);
+    let default_lang = match lookup_lang(&default_lang, &lang_aliases, |t| {
+        t == "en" || file_tags.contains(t)
+    }) {
+        Some(l) => l,
+        None => {
+            tracing::warn!(
+                "no {}/{}.json for DEFAULT_LANG, using en",
+                MESSAGES_DIR,
+                default_lang.tag()
+            );
+            sources.invalid("DEFAULT_LANG");
+            Lang::en()
         }
+    };
+
+    let catalog = load_message_catalog(MESSAGES_DIR, &default_lang);
+    // startup report: which keys each translation lacks
+    for (tag, keys) in &catalog.missing_keys {
+        tracing::warn!(
+            "messages/{}.json lacks {} key(s), using fallbacks: {}",
+            tag,
+            keys.len(),
+            keys.join(", ")
+        );
+    }
+
+    let mut all_messages = catalog.messages;
+    // Ensure at least English messages exist as a fallback
+    if !all_messages.contains_key("en") {
+        all_messages.insert(
+            "en".to_string(),
+            load_messages_file(&format!("{}/en.json", MESSAGES_DIR), &Lang::en()),
+        );
+    }
+    // placeholders of every translation against the English reference;
+    // MESSAGES_STRICT turns problems into a startup error
+    let placeholder_issues = validation::validate_placeholders(&all_messages);
+    for issue in &placeholder_issues {
+        tracing::warn!("{}", issue);
+    }
+    let messages_strict = sources
+        .var("MESSAGES_STRICT")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    if messages_strict && !placeholder_issues.is_empty() {
+        anyhow::bail!(
+            "Invalid messages: {} placeholder problem(s) found (MESSAGES_STRICT is set).",
+            placeholder_issues.len()
+        );
+    }
+
+    // which chats are served; /modalita and /chat_consentite change it at runtime
+    let operating_mode = match sources.var("OPERATING_MODE") {
+        Some(v) => match access::parse_operating_mode(&v) {
+            Some(mode) => mode,
+            None => anyhow::bail!(
+                "Invalid configuration: OPERATING_MODE ({}) must be public, allowlist or private.",
+                v
+            ),
+        },
+        None => OperatingMode::Public,
+    };
+    let allowed_chats = match sources.var("ALLOWED_CHATS") {
+        Some(v) => match access::parse_allowed_chats(&v) {
+            Some(chats) => chats,
+            None => anyhow::bail!(
+                "Invalid configuration: ALLOWED_CHATS ({}) must be comma separated chat ids.",
+                v
+            ),
+        },
+        None => BTreeSet::new(),
+    };
+    let leave_refused_chats = sources
+        .var("LEAVE_REFUSED_CHATS")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+
+    // token buckets per user and per chat; a zero burst or rate disables one
+    let rate_limits = RateLimits {
+        user: RateLimit::new(
+            sources.parse("RATE_LIMIT_USER_BURST", 5u32),
+            sources.parse("RATE_LIMIT_USER_PER_MIN", 30u32),
+        ),
+        chat: RateLimit::new(
+            sources.parse("RATE_LIMIT_CHAT_BURST", 20u32),
+            sources.parse("RATE_LIMIT_CHAT_PER_MIN", 60u32),
+        ),
+        notice_window: std::time::Duration::from_secs(u64::from(
+            sources.parse("THROTTLE_NOTICE_SECS", 60u32),
+        )),
+    };
+
+    let cfg = Config {
+        min: sources.parse("GAME_MIN", 1),
+        max: sources.parse("GAME_MAX", 100),
+        attempts: sources.parse("GAME_ATTEMPTS", 5),
+        restart_threshold: sources.parse("NUMBER_ATTEMPTS", 3),
+        lang: default_lang,
+        messages: all_messages.into(),
+        ttl_seconds: sources.parse("SEEN_WELCOME_TTL_SECS", 60 * 60 * 24 * 30),
+        bot_owner_id: sources.var("BOT_OWNER_ID").and_then(|v| {
+            let id = v.trim().parse().ok();
+            if id.is_none() {
+                sources.invalid("BOT_OWNER_ID");
+            }
+            id
+        }),
+        reset_user_starts: sources
+            .var("RESET_USER_STARTS")
+            .map(|s| {
+                s.split(',')
+                    .map(|p| p.trim().trim_matches('"').to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect::<HashSet<String>>()
+            })
+            .unwrap_or_default(),
+        season_period,
+        lang_aliases,
+        rate_limits,
+        config_sources: sources.settings,
+        operating_mode,
+        allowed_chats,
+        leave_refused_chats,
+    };
+    let shared_config = Arc::new(cfg);
+
+    if let Err(e) = shared_config.game_rules().validate() {
+        anyhow::bail!("Invalid configuration: {}", e);
+    }
+
+    // load persisted seen_welcome map
+    let data_dir = Path::new("data");
+    let seen_path = data_dir.join("seen_welcome.json");
+    let seen_welcome = load_seen_welcome(&seen_path);
+
+    // load persisted per-user start attempts map
+    let user_start_path = data_dir.join("user_start_attempts.json");
+    let user_start_attempts = load_user_start_attempts(&user_start_path);
+    // load persisted per-user miss streaks
+    let user_miss_path = data_dir.join("user_miss_streaks.json");
+    let user_miss_streaks = load_user_miss_streaks(&user_miss_path);
+    // load persisted per-user win streaks
+    let user_win_path = data_dir.join("user_win_streaks.json");
+    let user_win_streaks = load_user_win_streaks(&user_win_path);
+    // load persisted per-user statistics
+    let user_stats = rating::load_user_stats(&data_dir.join("user_stats.json"));
+    // load the current season (starting season 1 on first run) and the archive
+    let season_path = data_dir.join("season.json");
+    let season = match shared_config.season_period {
+        Some(period) => seasons::load_season(&season_path).unwrap_or_else(|| {
+            let season = SeasonState::new(period, now_unix());
+            seasons::save_season(&season_path, &season);
+            season
+        }),
+        None => SeasonState::default(),
+    };
+    let season_archive = seasons::load_season_archive(&data_dir.join("season_archive.json"));
+    // load group-level chat settings
+    let chat_settings = chat_settings::load_chat_settings(&data_dir.join("chat_settings.json"));
+    // load roles granted with /ruoli
+    let role_grants = roles::load_role_grants(&data_dir.join("roles.json"));
+    let audit_log = audit::load_audit_log(&data_dir.join("audit_log.jsonl"));
+
+    let state = Arc::new(RwLock::new(AppState {
+        by_user: HashMap::new(),
+        user_langs: HashMap::new(),
+        chat_settings,
+        chat_admins: AdminCache::default(),
+        seen_welcome,
+        user_start_attempts,
+        user_miss_streaks,
+        user_win_streaks,
+        user_stats,
+        season,
+        season_archive,
+        pending_erasures: HashMap::new(),
+        pending_resets: HashMap::new(),
+        keypad_input: HashMap::new(),
+        role_grants,
+        audit_log,
+    }));
+
+    tokio::spawn(run_season_scheduler(
+        bot.clone(),
+        state.clone(),
+        shared_config.clone(),
+    ));
+
+    let bans = bans::load_bans(&data_dir.join("bans.json"));
+    let maintenance = broadcast::load_maintenance(&data_dir.join("maintenance.json"));
+    if maintenance.is_some() {
+        tracing::warn!("starting in maintenance mode (/manutenzione off to end it)");
     }
+    let access = access::load_access(&data_dir.join("access.json"));
+    let guards: SharedGuards = Arc::new(Guards::new(&shared_config, bans, maintenance, access));
+
+    tokio::spawn(run_error_digest(
+        bot.clone(),
+        state.clone(),
+        shared_config.clone(),
+        guards.clone(),
+    ));
+
+    let handler = dptree::entry()
+        .branch(Update::filter_message().endpoint(
+            |bot: Bot,
+             msg: Message,
+             state: SharedState,
+             config: SharedConfig,
+             guards: SharedGuards| async move {
+                if let Err(err) =
+                    handle_message(&bot, &msg, state.clone(), config.clone(), guards.clone()).await
+                {
+                    tracing::error!("handler error: {:?}", err);
+                    guards.record_error();
+                    let command = msg.text().and_then(alerts::command_of);
+                    notify_owner_of_error(
+                        &bot,
+                        &state,
+                        &config,
+                        &guards,
+                        msg.chat.id.0,
+                        command,
+                        &err,
+                    )
+                    .await;
+                }
+                respond(())
+            },
+        ))
+        .branch(Update::filter_callback_query().endpoint(
+            |bot: Bot,
+             q: CallbackQuery,
+             state: SharedState,
+             config: SharedConfig,
+             guards: SharedGuards| async move {
+                if let Err(err) =
+                    handle_callback(&bot, &q, state.clone(), config.clone(), guards.clone()).await
+                {
+                    tracing::error!("callback handler error: {:?}", err);
+                    guards.record_error();
+                    let chat = q.message.as_ref().map(|m| m.chat().id.0).unwrap_or(0);
+                    // buttons are named after their callback data
+                    let command = q.data.as_ref().map(|d| format!("[{}]", d));
+                    notify_owner_of_error(&bot, &state, &config, &guards, chat, command, &err)
+                        .await;
+                }
+                respond(())
+            },
+        ));
+
+    Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![state, shared_config, guards])
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
 
     Ok(())
 }
 
-/// Main bot runner: load config, state, and start polling
 #[cfg(test)]
 mod ttl_tests {
     use super::*;
@@ -792,7 +3888,7 @@ mod ttl_tests {
 
         // Step 1: no persisted value -> start uses config_attempts
         let loaded = load_user_start_attempts(&tmp);
-        assert!(loaded.get(&composite).is_none());
+        assert!(!loaded.contains_key(&composite));
         let start_attempts = loaded.get(&composite).copied().unwrap_or(config_attempts);
         assert_eq!(start_attempts, config_attempts);
 
@@ -803,11 +3899,10 @@ mod ttl_tests {
         let reloaded = load_user_start_attempts(&tmp);
         assert_eq!(reloaded.get(&composite).copied().unwrap(), config_attempts);
 
-        // Step 3: simulate a quick win (used 1 attempt)
+        // Step 3: simulate a first win (no wins counted yet in the streak)
         let previous_start = config_attempts;
-        let remaining_after_guess = previous_start - 1; // e.g., guessed on first try
-        let next =
-            next_attempts_after_win(previous_start, remaining_after_guess, restart_threshold);
+        let win_streak = 0;
+        let next = next_attempts_for_win_streak(previous_start, win_streak, restart_threshold);
         assert_eq!(next, previous_start - 1);
         // persist the new start attempts
         let mut updated = reloaded.clone();
@@ -899,18 +3994,16 @@ mod ttl_tests {
         map.insert(composite.clone(), config_attempts);
         save_user_start_attempts(&tmp, &map);
 
-        // Simulate first quick win: used 1 attempt -> next should be 9
+        // Simulate first win: win streak 0 -> next should be 9
         let previous = config_attempts;
-        let remaining_after_guess = previous - 1;
-        let next1 = next_attempts_after_win(previous, remaining_after_guess, restart_threshold);
+        let next1 = next_attempts_for_win_streak(previous, 0, restart_threshold);
         assert_eq!(next1, previous - 1);
         map.insert(composite.clone(), next1);
         save_user_start_attempts(&tmp, &map);
 
-        // Simulate second quick win: used 1 attempt on 9 -> next should be 8
+        // Simulate second win: win streak 1 on 9 -> next should be 8
         let previous2 = next1;
-        let remaining_after_guess2 = previous2 - 1;
-        let next2 = next_attempts_after_win(previous2, remaining_after_guess2, restart_threshold);
+        let next2 = next_attempts_for_win_streak(previous2, 1, restart_threshold);
         assert_eq!(next2, previous2 - 1);
         map.insert(composite.clone(), next2);
         save_user_start_attempts(&tmp, &map);
@@ -925,126 +4018,3 @@ mod ttl_tests {
         let _ = fs::remove_file(&tmp);
     }
 }
-
-/// Run the bot (previously in main). Separated so binaries can call this and
-/// tests/integration can import the library.
-pub async fn run_bot() -> Result<()> {
-    tracing_subscriber::fmt::init();
-    dotenv().ok();
-    let bot = Bot::from_env();
-
-    let default_lang = env::var("DEFAULT_LANG")
-        .ok()
-        .unwrap_or_else(|| "en".to_string());
-    let default_lang = if default_lang.to_lowercase().starts_with("it") {
-        Lang::It
-    } else {
-        Lang::En
-    };
-
-    let mut cfg = Config {
-        min: env::var("GAME_MIN")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(1),
-        max: env::var("GAME_MAX")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(100),
-        attempts: env::var("GAME_ATTEMPTS")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(5),
-        restart_threshold: env::var("NUMBER_ATTEMPTS")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(3),
-        lang: default_lang,
-        messages: load_all_messages("messages"),
-        ttl_seconds: env::var("SEEN_WELCOME_TTL_SECS")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(60 * 60 * 24 * 30),
-        bot_owner_id: env::var("BOT_OWNER_ID").ok().and_then(|v| v.parse().ok()),
-        reset_user_starts: env::var("RESET_USER_STARTS")
-            .ok()
-            .map(|s| {
-                s.split(',')
-                    .map(|p| p.trim().trim_matches('"').to_string())
-                    .filter(|p| !p.is_empty())
-                    .collect::<HashSet<String>>()
-            })
-            .unwrap_or_default(),
-    };
-    // Ensure at least English messages exist as a fallback
-    if !cfg.messages.contains_key("en") {
-        cfg.messages.insert(
-            "en".to_string(),
-            load_messages_file("messages/en.json", Lang::En),
-        );
-    }
-    // Ensure default language exists in the map; if not, insert fallback
-    let cfg_lang_tag = lang_tag(&cfg.lang).to_string();
-    if !cfg.messages.contains_key(&cfg_lang_tag) {
-        cfg.messages.insert(
-            cfg_lang_tag.clone(),
-            cfg.messages.get("en").unwrap().clone(),
-        );
-    }
-    let shared_config = Arc::new(cfg);
-
-    if shared_config.min >= shared_config.max {
-        anyhow::bail!(
-            "Invalid configuration: GAME_MIN ({}) must be less than GAME_MAX ({}).",
-            shared_config.min,
-            shared_config.max
-        );
-    }
-    if shared_config.attempts <= 0 {
-        anyhow::bail!(
-            "Invalid configuration: GAME_ATTEMPTS ({}) must be a positive integer.",
-            shared_config.attempts
-        );
-    }
-    if shared_config.restart_threshold < 0 {
-        anyhow::bail!(
-            "Invalid configuration: NUMBER_ATTEMPTS ({}) must be a non-negative integer.",
-            shared_config.restart_threshold
-        );
-    }
-
-    // load persisted seen_welcome map
-    let data_dir = Path::new("data");
-    let seen_path = data_dir.join("seen_welcome.json");
-    let seen_welcome = load_seen_welcome(&seen_path);
-
-    // load persisted per-user start attempts map
-    let user_start_path = data_dir.join("user_start_attempts.json");
-    let user_start_attempts = load_user_start_attempts(&user_start_path);
-    // load persisted per-user miss streaks
-    let user_miss_path = data_dir.join("user_miss_streaks.json");
-    let user_miss_streaks = load_user_miss_streaks(&user_miss_path);
-
-    let state = Arc::new(RwLock::new(AppState {
-        by_user: HashMap::new(),
-        user_langs: HashMap::new(),
-        chat_langs: HashMap::new(),
-        seen_welcome,
-        user_start_attempts,
-        user_miss_streaks,
-    }));
-
-    teloxide::repl(bot, move |bot: Bot, msg: Message| {
-        let state = state.clone();
-        let shared_config = shared_config.clone();
-        async move {
-            if let Err(err) = handle_message(&bot, &msg, state, shared_config).await {
-                tracing::error!("handler error: {:?}", err);
-            }
-            respond(())
-        }
-    })
-    .await;
-
-    Ok(())
-}
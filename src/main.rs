@@ -1,6 +1,13 @@
-use telegram_bot_rust::run_bot;
+use telegram_bot_rust::{run_bot, validation};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    run_bot().await
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        // `telegram-bot-rust check-messages [dir]` validates the message files
+        Some("check-messages") => {
+            validation::check_messages(args.get(1).map(String::as_str).unwrap_or("messages"))
+        }
+        _ => run_bot().await,
+    }
 }
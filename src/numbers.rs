@@ -0,0 +1,422 @@
+use std::fmt::Display;
+
+/// Highest number accepted as a spelled-out word ("forty-two", "сорок два")
+const MAX_NUMBER_WORD: i32 = 100;
+
+/// Value of a decimal digit in any of the digit systems users type: ASCII,
+/// Arabic-Indic (٤٢), Extended Arabic-Indic (۴۲, Persian/Urdu), Devanagari
+/// and full-width (４２).
+fn digit_value(c: char) -> Option<u32> {
+    let zero = match c {
+        '0'..='9' => '0',
+        '\u{0660}'..='\u{0669}' => '\u{0660}',
+        '\u{06F0}'..='\u{06F9}' => '\u{06F0}',
+        '\u{0966}'..='\u{096F}' => '\u{0966}',
+        '\u{FF10}'..='\u{FF19}' => '\u{FF10}',
+        _ => return None,
+    };
+    Some(c as u32 - zero as u32)
+}
+
+/// Parse a guess typed in any digit system, as Chinese numerals or as a
+/// number word of the user's language `tag`. A sign, surrounding spaces and
+/// a trailing full stop or exclamation mark ("42.", "٤٢!") are accepted.
+pub fn parse_guess(text: &str, tag: &str) -> Option<i32> {
+    let text = text
+        .trim()
+        .trim_end_matches(['.', '!', '。', '！', '۔'])
+        .trim_end();
+    let (negative, body) = match text.strip_prefix(['-', '−', '－']) {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, text.strip_prefix(['+', '＋']).unwrap_or(text)),
+    };
+    let n = parse_digits(body)
+        .or_else(|| parse_chinese(body))
+        .or_else(|| parse_number_word(body, tag))?;
+    if negative { n.checked_neg() } else { Some(n) }
+}
+
+/// Parse a number formatted by [`format_number`] (or plain ASCII digits);
+/// used to pick plural forms of already localized values.
+pub fn parse_localized(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let mut n: i64 = 0;
+    let mut any = false;
+    for c in body.chars() {
+        if is_group_separator(c) {
+            continue;
+        }
+        n = n.checked_mul(10)?.checked_add(digit_value(c)? as i64)?;
+        any = true;
+    }
+    if !any {
+        return None;
+    }
+    Some(if negative { -n } else { n })
+}
+
+fn parse_digits(s: &str) -> Option<i32> {
+    if s.is_empty() {
+        return None;
+    }
+    s.chars().try_fold(0i32, |n, c| {
+        n.checked_mul(10)?.checked_add(digit_value(c)? as i32)
+    })
+}
+
+fn chinese_digit(c: char) -> Option<i32> {
+    Some(match c {
+        '零' | '〇' => 0,
+        '一' | '壹' => 1,
+        '二' | '两' | '兩' | '贰' | '貳' => 2,
+        '三' | '叁' | '參' => 3,
+        '四' | '肆' => 4,
+        '五' | '伍' => 5,
+        '六' | '陆' | '陸' => 6,
+        '七' | '柒' => 7,
+        '八' | '捌' => 8,
+        '九' | '玖' => 9,
+        _ => return None,
+    })
+}
+
+/// Chinese numerals, both positional (四二) and with units (四十二, 一百零五,
+/// 十二, 三万五千)
+fn parse_chinese(s: &str) -> Option<i32> {
+    if s.is_empty() {
+        return None;
+    }
+    if s.chars().all(|c| chinese_digit(c).is_some()) {
+        return s.chars().try_fold(0i32, |n, c| {
+            n.checked_mul(10)?.checked_add(chinese_digit(c)?)
+        });
+    }
+    let (mut total, mut section, mut digit) = (0i64, 0i64, None::<i64>);
+    for c in s.chars() {
+        if let Some(d) = chinese_digit(c) {
+            digit = Some(d as i64);
+            continue;
+        }
+        let unit = match c {
+            '十' | '拾' => 10,
+            '百' | '佰' => 100,
+            '千' | '仟' => 1_000,
+            '万' | '萬' => 10_000,
+            '亿' | '億' => 100_000_000,
+            _ => return None,
+        };
+        if unit >= 10_000 {
+            // 万 and 亿 always follow a number (一万, not 万)
+            section += digit.take().unwrap_or(0);
+            if total == 0 && section == 0 {
+                return None;
+            }
+            total = total.checked_add(section)?.checked_mul(unit)?;
+            section = 0;
+        } else {
+            // a bare 十 means ten (十二 = 12)
+            section = section.checked_add(digit.take().unwrap_or(1) * unit)?;
+        }
+    }
+    i32::try_from(total.checked_add(section + digit.unwrap_or(0))?).ok()
+}
+
+/// Lowercase, drop diacritics that users often omit and unify the spellings
+/// that differ only in orthography, so "Ventitré", "ventitre", "сорок два",
+/// "اثنان و أربعون" match the generated words.
+fn normalize_word(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.to_lowercase().chars() {
+        match c {
+            'à' | 'á' => out.push('a'),
+            'è' | 'é' => out.push('e'),
+            'ì' | 'í' => out.push('i'),
+            'ò' | 'ó' => out.push('o'),
+            'ù' | 'ú' => out.push('u'),
+            'ё' => out.push('е'),
+            'أ' | 'إ' | 'آ' => out.push('ا'),
+            'ى' => out.push('ي'),
+            // Arabic short vowels and tatweel
+            '\u{064B}'..='\u{0652}' | '\u{0640}' => {}
+            '-' | '\u{2010}' | '\u{2011}' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    let words: Vec<&str> = out.split_whitespace().collect();
+    // Arabic joins tens with "و" ("and"), written attached or apart
+    words.join(" ").replace(" و ", " و")
+}
+
+fn parse_number_word(s: &str, tag: &str) -> Option<i32> {
+    let primary = tag
+        .split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let spell: fn(i32) -> Vec<String> = match primary.as_str() {
+        "en" => spell_en,
+        "it" => spell_it,
+        "ru" => spell_ru,
+        "ar" => spell_ar,
+        _ => return None,
+    };
+    let wanted = normalize_word(s);
+    (0..=MAX_NUMBER_WORD).find(|&n| spell(n).iter().any(|w| normalize_word(w) == wanted))
+}
+
+const EN_UNITS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const EN_TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// English spellings of `n` (0..=100)
+fn spell_en(n: i32) -> Vec<String> {
+    match n {
+        0..=19 => vec![EN_UNITS[n as usize].to_string()],
+        100 => vec!["one hundred".to_string(), "hundred".to_string()],
+        _ if n % 10 == 0 => vec![EN_TENS[(n / 10) as usize].to_string()],
+        _ => vec![format!(
+            "{}-{}",
+            EN_TENS[(n / 10) as usize],
+            EN_UNITS[(n % 10) as usize]
+        )],
+    }
+}
+
+const IT_UNITS: [&str; 20] = [
+    "zero",
+    "uno",
+    "due",
+    "tre",
+    "quattro",
+    "cinque",
+    "sei",
+    "sette",
+    "otto",
+    "nove",
+    "dieci",
+    "undici",
+    "dodici",
+    "tredici",
+    "quattordici",
+    "quindici",
+    "sedici",
+    "diciassette",
+    "diciotto",
+    "diciannove",
+];
+const IT_TENS: [&str; 10] = [
+    "",
+    "",
+    "venti",
+    "trenta",
+    "quaranta",
+    "cinquanta",
+    "sessanta",
+    "settanta",
+    "ottanta",
+    "novanta",
+];
+
+/// Italian spellings of `n` (0..=100); tens drop their vowel before "uno"
+/// and "otto" (ventuno, ventotto)
+fn spell_it(n: i32) -> Vec<String> {
+    match n {
+        0..=19 => vec![IT_UNITS[n as usize].to_string()],
+        100 => vec!["cento".to_string()],
+        _ => {
+            let tens = IT_TENS[(n / 10) as usize];
+            match n % 10 {
+                0 => vec![tens.to_string()],
+                u @ (1 | 8) => vec![format!(
+                    "{}{}",
+                    &tens[..tens.len() - 1],
+                    IT_UNITS[u as usize]
+                )],
+                3 => vec![format!("{}tré", tens)],
+                u => vec![format!("{}{}", tens, IT_UNITS[u as usize])],
+            }
+        }
+    }
+}
+
+const RU_UNITS: [&str; 20] = [
+    "ноль",
+    "один",
+    "два",
+    "три",
+    "четыре",
+    "пять",
+    "шесть",
+    "семь",
+    "восемь",
+    "девять",
+    "десять",
+    "одиннадцать",
+    "двенадцать",
+    "тринадцать",
+    "четырнадцать",
+    "пятнадцать",
+    "шестнадцать",
+    "семнадцать",
+    "восемнадцать",
+    "девятнадцать",
+];
+const RU_TENS: [&str; 10] = [
+    "",
+    "",
+    "двадцать",
+    "тридцать",
+    "сорок",
+    "пятьдесят",
+    "шестьдесят",
+    "семьдесят",
+    "восемьдесят",
+    "девяносто",
+];
+
+/// Russian spellings of `n` (0..=100)
+fn spell_ru(n: i32) -> Vec<String> {
+    match n {
+        0..=19 => vec![RU_UNITS[n as usize].to_string()],
+        100 => vec!["сто".to_string()],
+        _ if n % 10 == 0 => vec![RU_TENS[(n / 10) as usize].to_string()],
+        _ => vec![format!(
+            "{} {}",
+            RU_TENS[(n / 10) as usize],
+            RU_UNITS[(n % 10) as usize]
+        )],
+    }
+}
+
+const AR_UNITS: [&str; 11] = [
+    "صفر",
+    "واحد",
+    "اثنان",
+    "ثلاثة",
+    "أربعة",
+    "خمسة",
+    "ستة",
+    "سبعة",
+    "ثمانية",
+    "تسعة",
+    "عشرة",
+];
+const AR_TENS: [&str; 10] = [
+    "",
+    "",
+    "عشرون",
+    "ثلاثون",
+    "أربعون",
+    "خمسون",
+    "ستون",
+    "سبعون",
+    "ثمانون",
+    "تسعون",
+];
+
+/// Arabic spellings of `n` (0..=100), masculine forms; compound numbers
+/// put the unit first ("اثنان وأربعون"). The spoken genitive forms
+/// ("اثنين وأربعين") are accepted as well.
+fn spell_ar(n: i32) -> Vec<String> {
+    let mut words = spell_ar_nominative(n);
+    let genitive: Vec<String> = words
+        .iter()
+        .map(|w| {
+            w.split(' ')
+                .map(|part| match part.strip_suffix("ون") {
+                    Some(stem) => format!("{}ين", stem),
+                    None => part.replace("اثنان", "اثنين").replace("اثنا", "اثني"),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    words.extend(genitive);
+    words
+}
+
+fn spell_ar_nominative(n: i32) -> Vec<String> {
+    match n {
+        0..=10 => vec![AR_UNITS[n as usize].to_string()],
+        11 => vec!["أحد عشر".to_string()],
+        12 => vec!["اثنا عشر".to_string()],
+        13..=19 => vec![format!("{} عشر", AR_UNITS[(n % 10) as usize])],
+        100 => vec!["مائة".to_string(), "مئة".to_string()],
+        _ if n % 10 == 0 => vec![AR_TENS[(n / 10) as usize].to_string()],
+        _ => vec![format!(
+            "{} و{}",
+            AR_UNITS[(n % 10) as usize],
+            AR_TENS[(n / 10) as usize]
+        )],
+    }
+}
+
+fn is_group_separator(c: char) -> bool {
+    matches!(c, ',' | '.' | '\u{066C}' | '\u{00A0}' | '\u{202F}' | ' ')
+}
+
+/// Digits and thousands separator used for numbers in replies
+fn number_style(tag: &str) -> (char, &'static str) {
+    let primary = tag
+        .split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match primary.as_str() {
+        "ar" => ('\u{0660}', "\u{066C}"),
+        "fa" | "ur" => ('\u{06F0}', "\u{066C}"),
+        "it" | "de" | "es" | "pt" | "nl" | "tr" | "id" => ('0', "."),
+        "ru" | "uk" | "be" | "fr" | "pl" | "cs" | "sv" | "fi" => ('0', "\u{202F}"),
+        _ => ('0', ","),
+    }
+}
+
+/// Format an integer for replies in the language `tag`: native digits
+/// (Arabic-Indic for Arabic) and the language's thousands separator. Anything
+/// that is not a plain integer is returned unchanged.
+pub fn format_number(value: impl Display, tag: &str) -> String {
+    let s = value.to_string();
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", s.as_str()),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return s;
+    }
+    let (zero, separator) = number_style(tag);
+    let mut out = String::from(sign);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push_str(separator);
+        }
+        let d = c as u32 - '0' as u32;
+        out.push(char::from_u32(zero as u32 + d).unwrap_or(c));
+    }
+    out
+}
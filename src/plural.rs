@@ -0,0 +1,178 @@
+use crate::bidi;
+use crate::format_with;
+use crate::numbers;
+
+/// CLDR plural categories
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    fn keyword(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Plural category of the integer `n` in the language `tag` (CLDR cardinal
+/// rules for integers). Only the primary subtag is considered; languages
+/// without a specific rule use the English one/other split.
+pub fn plural_category(tag: &str, n: i64) -> PluralCategory {
+    let n = n.unsigned_abs();
+    let primary = tag.split(['-', '_']).next().unwrap_or("").to_lowercase();
+    match primary.as_str() {
+        "zh" | "ja" | "ko" | "vi" | "th" | "id" => PluralCategory::Other,
+        "ru" | "uk" | "be" => {
+            let (m10, m100) = (n % 10, n % 100);
+            if m10 == 1 && m100 != 11 {
+                PluralCategory::One
+            } else if (2..=4).contains(&m10) && !(12..=14).contains(&m100) {
+                PluralCategory::Few
+            } else {
+                PluralCategory::Many
+            }
+        }
+        "ar" => match (n, n % 100) {
+            (0, _) => PluralCategory::Zero,
+            (1, _) => PluralCategory::One,
+            (2, _) => PluralCategory::Two,
+            (_, 3..=10) => PluralCategory::Few,
+            (_, 11..=99) => PluralCategory::Many,
+            _ => PluralCategory::Other,
+        },
+        "fr" | "pt" if n <= 1 => PluralCategory::One,
+        "fr" | "pt" => PluralCategory::Other,
+        _ if n == 1 => PluralCategory::One,
+        _ => PluralCategory::Other,
+    }
+}
+
+/// Fill a message template for the language `tag`.
+///
+/// Besides plain `{key}` placeholders, templates may use ICU-style blocks:
+/// `{key, plural, one {# attempt} other {# attempts}}` picks the arm for the
+/// plural category of the value (`=N` arms match exact values first, `#` is
+/// replaced by the number; values formatted with [`numbers::format_number`]
+/// are understood) and `{key, select, a {...} other {...}}` picks the
+/// arm named after the value. Malformed blocks are left untouched.
+///
+/// For right-to-left languages every substituted value (and `#`) is wrapped
+/// in directional isolates, so names and numbers do not scramble the text.
+pub fn format_message(template: &str, tag: &str, pairs: &[(&str, &str)]) -> String {
+    let expanded = expand_blocks(template, tag, pairs);
+    if !bidi::is_rtl(tag) {
+        return format_with(&expanded, pairs);
+    }
+    let isolated: Vec<(&str, String)> = pairs.iter().map(|(k, v)| (*k, bidi::isolate(v))).collect();
+    let isolated: Vec<(&str, &str)> = isolated.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    format_with(&expanded, &isolated)
+}
+
+/// Replace every plural/select block of `template` with its chosen arm
+fn expand_blocks(template: &str, tag: &str, pairs: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let Some(close) = matching_brace(&rest[open..]).map(|c| open + c) else {
+            out.push_str(&rest[open..]);
+            return out;
+        };
+        let inner = &rest[open + 1..close];
+        match choose_arm(inner, tag, pairs) {
+            Some(arm) => out.push_str(&expand_blocks(&arm, tag, pairs)),
+            None => out.push_str(&rest[open..=close]),
+        }
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Byte offset of the brace closing the one `s` starts with
+pub(crate) fn matching_brace(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse the `selector {text}` arms of a block
+pub(crate) fn parse_arms(mut s: &str) -> Option<Vec<(&str, &str)>> {
+    let mut arms = Vec::new();
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            return Some(arms);
+        }
+        let open = s.find('{')?;
+        let selector = s[..open].trim();
+        if selector.is_empty() || selector.contains(char::is_whitespace) {
+            return None;
+        }
+        let close = open + matching_brace(&s[open..])?;
+        arms.push((selector, &s[open + 1..close]));
+        s = &s[close + 1..];
+    }
+}
+
+/// A parsed `{key, plural|select, selector {text} ...}` block
+pub(crate) struct Block<'a> {
+    pub key: &'a str,
+    pub kind: &'a str,
+    /// `(selector, text)` pairs in template order
+    pub arms: Vec<(&'a str, &'a str)>,
+}
+
+/// Parse the body of a `plural`/`select` block (without the outer braces)
+pub(crate) fn parse_block(inner: &str) -> Option<Block<'_>> {
+    let mut parts = inner.splitn(3, ',');
+    let key = parts.next()?.trim();
+    let kind = parts.next()?.trim();
+    if !matches!(kind, "plural" | "select") {
+        return None;
+    }
+    let arms = parse_arms(parts.next()?)?;
+    Some(Block { key, kind, arms })
+}
+
+/// Text of the arm chosen for a block, or `None` when `inner` is not a
+/// block or its key has no value.
+fn choose_arm(inner: &str, tag: &str, pairs: &[(&str, &str)]) -> Option<String> {
+    let Block { key, kind, arms } = parse_block(inner)?;
+    let value = pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)?;
+    let find = |selector: &str| arms.iter().find(|(s, _)| *s == selector).map(|(_, t)| *t);
+    if kind == "select" {
+        return find(value).or_else(|| find("other")).map(str::to_string);
+    }
+    let n = numbers::parse_localized(value)?;
+    let arm = find(&format!("={}", n))
+        .or_else(|| find(plural_category(tag, n).keyword()))
+        .or_else(|| find("other"))?;
+    if bidi::is_rtl(tag) {
+        return Some(arm.replace('#', &bidi::isolate(value)));
+    }
+    Some(arm.replace('#', value))
+}
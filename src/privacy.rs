@@ -0,0 +1,142 @@
+use crate::{
+    AppState,
+    bans::{Ban, BanList},
+    roles::Role,
+};
+use serde_json::{Map, Value, json};
+
+/// Whether a "chat:user" composite key belongs to `user_id`
+fn key_is_user(key: &str, user_id: u64) -> bool {
+    key.rsplit_once(':')
+        .and_then(|(_, u)| u.parse::<u64>().ok())
+        .map(|u| u == user_id)
+        .unwrap_or(false)
+}
+
+/// Collect the entries of a "chat:user" keyed map that belong to `user_id`,
+/// re-keyed by chat id.
+fn user_entries<V: serde::Serialize>(
+    map: &std::collections::HashMap<String, V>,
+    user_id: u64,
+) -> Value {
+    let mut out = Map::new();
+    for (k, v) in map.iter().filter(|(k, _)| key_is_user(k, user_id)) {
+        let chat = k.rsplit_once(':').map(|(c, _)| c).unwrap_or(k);
+        out.insert(chat.to_string(), json!(v));
+    }
+    Value::Object(out)
+}
+
+/// Everything the bot stores about a user, across all chats, as a JSON
+/// document for `/miei_dati`. The target of an active game is left out so the
+/// export cannot be used to cheat, and the bans against the user do not name
+/// who issued them.
+pub fn export_user_data(state: &AppState, bans: &BanList, user_id: u64) -> Value {
+    let mut games = Map::new();
+    for ((chat, _), g) in state.by_user.iter().filter(|((_, u), _)| *u == user_id) {
+        games.insert(
+            chat.to_string(),
+            json!({
+                "attempts_left": g.attempts_left,
+                "start_attempts": g.start_attempts,
+                "guesses": g.guesses.iter().map(|x| x.value).collect::<Vec<_>>(),
+            }),
+        );
+    }
+    let mut langs = Map::new();
+    for ((chat, _), l) in state.user_langs.iter().filter(|((_, u), _)| *u == user_id) {
+        langs.insert(chat.to_string(), json!(l.tag()));
+    }
+    let champion_of: Vec<Value> = state
+        .season_archive
+        .iter()
+        .flat_map(|r| {
+            r.champions
+                .iter()
+                .filter(|(_, c)| c.user_id == user_id)
+                .map(move |(chat, c)| json!({"season": r.number, "chat": chat, "points": c.points, "wins": c.wins}))
+        })
+        .collect();
+    let ban_json = |b: &Ban| json!({"at": b.at, "until": b.until, "reason": b.reason});
+    let chat_bans: Map<String, Value> = bans
+        .chats
+        .iter()
+        .filter_map(|(chat, b)| Some((chat.to_string(), ban_json(b.get(&user_id)?))))
+        .collect();
+    json!({
+        "user_id": user_id,
+        "active_games": Value::Object(games),
+        "language_preferences": Value::Object(langs),
+        "seen_welcome": user_entries(&state.seen_welcome, user_id),
+        "user_start_attempts": user_entries(&state.user_start_attempts, user_id),
+        "user_miss_streaks": user_entries(&state.user_miss_streaks, user_id),
+        "user_win_streaks": user_entries(&state.user_win_streaks, user_id),
+        "user_stats": user_entries(&state.user_stats, user_id),
+        "season_scores": user_entries(&state.season.scores, user_id),
+        "season_champion_of": champion_of,
+        "roles": {
+            "global_admin": state.role_grants.global_admins.contains(&user_id),
+            "moderator_of": state.role_grants.moderated_chats(user_id),
+        },
+        // kept on erasure, or erasing would lift them
+        "bans": {
+            "global": bans.global.get(&user_id).map(ban_json),
+            "chats": Value::Object(chat_bans),
+        },
+        // kept on erasure: the audit log records who did what
+        "audit_log": state
+            .audit_log
+            .iter()
+            .filter(|e| e.actor == Some(user_id))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Remove every entry about `user_id` from memory, returning how many entries
+/// were erased. Callers persist the data files afterwards.
+///
+/// This includes the roles granted to the user, a half typed keypad guess
+/// and the user's own pending `/cancella_dati` and `/reset_starts`
+/// confirmations. Kept: the audit log, which records who did what, an
+/// erasure of this user requested by an admin, which belongs to the admin,
+/// and the bans against the user (`data/bans.json`, not part of
+/// [`AppState`]), or erasing one's data would lift them.
+pub fn erase_user_data(state: &mut AppState, user_id: u64) -> usize {
+    let mut removed = 0;
+    let before = state.by_user.len();
+    state.by_user.retain(|(_, u), _| *u != user_id);
+    removed += before - state.by_user.len();
+    let before = state.user_langs.len();
+    state.user_langs.retain(|(_, u), _| *u != user_id);
+    removed += before - state.user_langs.len();
+    let before = state.keypad_input.len();
+    state.keypad_input.retain(|(_, u), _| *u != user_id);
+    removed += before - state.keypad_input.len();
+    removed += usize::from(state.pending_erasures.remove(&user_id).is_some());
+    removed += usize::from(state.pending_resets.remove(&user_id).is_some());
+    removed += usize::from(state.role_grants.global_admins.remove(&user_id));
+    for chat in state.role_grants.moderated_chats(user_id) {
+        removed += usize::from(state.role_grants.revoke(chat, user_id, Role::Moderator));
+    }
+
+    macro_rules! erase_composite {
+        ($map:expr) => {{
+            let before = $map.len();
+            $map.retain(|k, _| !key_is_user(k, user_id));
+            removed += before - $map.len();
+        }};
+    }
+    erase_composite!(state.seen_welcome);
+    erase_composite!(state.user_start_attempts);
+    erase_composite!(state.user_miss_streaks);
+    erase_composite!(state.user_win_streaks);
+    erase_composite!(state.user_stats);
+    erase_composite!(state.season.scores);
+
+    for record in state.season_archive.iter_mut() {
+        let before = record.champions.len();
+        record.champions.retain(|_, c| c.user_id != user_id);
+        removed += before - record.champions.len();
+    }
+    removed
+}
@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// A token bucket: `burst` messages at once, refilled at `per_minute`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_minute: u32,
+}
+
+impl RateLimit {
+    /// From the `*_BURST` and `*_PER_MIN` values; a zero disables the limit
+    pub fn new(burst: u32, per_minute: u32) -> Option<Self> {
+        (burst > 0 && per_minute > 0).then_some(RateLimit { burst, per_minute })
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        f64::from(self.per_minute) / 60.0
+    }
+}
+
+/// The limits applied before any message reaches the game
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimits {
+    /// per user, across all chats (RATE_LIMIT_USER_BURST, RATE_LIMIT_USER_PER_MIN)
+    pub user: Option<RateLimit>,
+    /// per chat, all users together (RATE_LIMIT_CHAT_BURST, RATE_LIMIT_CHAT_PER_MIN)
+    pub chat: Option<RateLimit>,
+    /// at most one throttle notice per user (or chat) in this window
+    /// (THROTTLE_NOTICE_SECS)
+    pub notice_window: Duration,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        RateLimits {
+            user: RateLimit::new(5, 30),
+            chat: RateLimit::new(20, 60),
+            notice_window: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        TokenBucket {
+            tokens: f64::from(limit.burst),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.refill_per_sec()).min(f64::from(limit.burst));
+        self.updated = now;
+    }
+
+    fn is_full(&self, limit: &RateLimit) -> bool {
+        self.tokens >= f64::from(limit.burst)
+    }
+}
+
+/// What to do with an incoming update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// drop it; `notify` is set for the first drop of the notice window
+    Drop {
+        limited: Limited,
+        notify: bool,
+    },
+}
+
+/// Which bucket ran out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limited {
+    User,
+    Chat,
+}
+
+/// How often idle buckets and old notices are forgotten
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Token buckets per user and per chat. Kept outside of `AppState` so that
+/// a flood never reaches the state lock.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    users: HashMap<u64, TokenBucket>,
+    chats: HashMap<i64, TokenBucket>,
+    // last throttle notice, key: (chat_id, Some(user) or None for the chat)
+    notices: HashMap<(i64, Option<u64>), Instant>,
+    last_prune: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        RateLimiter {
+            limits,
+            users: HashMap::new(),
+            chats: HashMap::new(),
+            notices: HashMap::new(),
+            last_prune: None,
+        }
+    }
+
+    /// Take a token from the user's and the chat's bucket, or tell why the
+    /// update must be dropped. A dropped update takes no token, so a user
+    /// over the limit does not drain the chat's bucket.
+    pub fn check(&mut self, chat_id: i64, user_id: Option<u64>, now: Instant) -> Verdict {
+        self.prune(now);
+        let user_limit = self.limits.user.zip(user_id);
+        if let Some((limit, id)) = user_limit {
+            let bucket = self
+                .users
+                .entry(id)
+                .or_insert_with(|| TokenBucket::full(&limit, now));
+            bucket.refill(&limit, now);
+            if bucket.tokens < 1.0 {
+                return self.dropped(Limited::User, chat_id, user_id, now);
+            }
+        }
+        if let Some(limit) = self.limits.chat {
+            let bucket = self
+                .chats
+                .entry(chat_id)
+                .or_insert_with(|| TokenBucket::full(&limit, now));
+            bucket.refill(&limit, now);
+            if bucket.tokens < 1.0 {
+                return self.dropped(Limited::Chat, chat_id, None, now);
+            }
+            bucket.tokens -= 1.0;
+        }
+        if let Some((_, id)) = user_limit
+            && let Some(bucket) = self.users.get_mut(&id)
+        {
+            bucket.tokens -= 1.0;
+        }
+        Verdict::Allow
+    }
+
+    fn dropped(
+        &mut self,
+        limited: Limited,
+        chat_id: i64,
+        user_id: Option<u64>,
+        now: Instant,
+    ) -> Verdict {
+        let window = self.limits.notice_window;
+        let last = self.notices.get(&(chat_id, user_id));
+        let notify = last.is_none_or(|t| now.saturating_duration_since(*t) >= window);
+        if notify {
+            self.notices.insert((chat_id, user_id), now);
+        }
+        Verdict::Drop { limited, notify }
+    }
+
+    /// Forget full buckets and expired notices, at most every PRUNE_INTERVAL
+    fn prune(&mut self, now: Instant) {
+        let due = self
+            .last_prune
+            .is_none_or(|t| now.saturating_duration_since(t) >= PRUNE_INTERVAL);
+        if !due {
+            return;
+        }
+        self.last_prune = Some(now);
+        if let Some(limit) = self.limits.user {
+            self.users.retain(|_, b| {
+                b.refill(&limit, now);
+                !b.is_full(&limit)
+            });
+        }
+        if let Some(limit) = self.limits.chat {
+            self.chats.retain(|_, b| {
+                b.refill(&limit, now);
+                !b.is_full(&limit)
+            });
+        }
+        let window = self.limits.notice_window;
+        self.notices
+            .retain(|_, t| now.saturating_duration_since(*t) < window);
+    }
+
+    /// Number of users and chats with a bucket in memory
+    pub fn tracked(&self) -> (usize, usize) {
+        (self.users.len(), self.chats.len())
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+/// A guess together with the interval that was still possible when it was made
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Guess {
+    pub value: i32,
+    pub low: i32,
+    pub high: i32,
+}
+
+impl Guess {
+    /// A guess outside the still-possible interval gives no new information
+    pub fn is_wasted(&self) -> bool {
+        self.value < self.low || self.value > self.high
+    }
+}
+
+/// How well a finished game used its guesses compared with bisection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameRating {
+    pub guesses: u32,
+    pub wasted: u32,
+    /// 0..=100, average of the per-guess quality
+    pub efficiency: u32,
+    /// guesses an optimal bisection needs for the same target and range
+    pub optimal_guesses: u32,
+}
+
+/// Quality of a single guess in [0, 1]: how many candidates it rules out in
+/// the worst case compared with guessing the midpoint of the interval.
+pub fn guess_quality(guess: &Guess) -> f64 {
+    if guess.is_wasted() {
+        return 0.0;
+    }
+    let n = (guess.high - guess.low) as i64 + 1;
+    let worst = std::cmp::max(guess.value - guess.low, guess.high - guess.value) as i64;
+    let best = n / 2;
+    (n - worst) as f64 / (n - best) as f64
+}
+
+/// Number of guesses bisection needs to find `target` in [low, high],
+/// always guessing the lower midpoint.
+pub fn bisection_guesses(mut low: i32, mut high: i32, target: i32) -> u32 {
+    let mut count = 0;
+    while low <= high {
+        count += 1;
+        let mid = low + (high - low) / 2;
+        if mid == target {
+            break;
+        } else if mid < target {
+            low = mid + 1;
+        } else {
+            high = mid - 1;
+        }
+    }
+    count
+}
+
+/// Rate the guesses of a finished game
+pub fn rate_game(guesses: &[Guess], target: i32) -> GameRating {
+    let wasted = guesses.iter().filter(|g| g.is_wasted()).count() as u32;
+    let efficiency = if guesses.is_empty() {
+        0
+    } else {
+        let total: f64 = guesses.iter().map(guess_quality).sum();
+        (total / guesses.len() as f64 * 100.0).round() as u32
+    };
+    let optimal_guesses = guesses
+        .first()
+        .map(|g| bisection_guesses(g.low, g.high, target))
+        .unwrap_or(0);
+    GameRating {
+        guesses: guesses.len() as u32,
+        wasted,
+        efficiency,
+        optimal_guesses,
+    }
+}
+
+/// Lifetime statistics of a user in a chat, persisted to `data/user_stats.json`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserStats {
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub guesses: u32,
+    pub wasted_guesses: u32,
+    /// sum of the per-game efficiency percentages
+    pub efficiency_total: u32,
+}
+
+impl UserStats {
+    /// Add a finished game
+    pub fn record(&mut self, won: bool, rating: &GameRating) {
+        self.games += 1;
+        if won {
+            self.wins += 1;
+        } else {
+            self.losses += 1;
+        }
+        self.guesses += rating.guesses;
+        self.wasted_guesses += rating.wasted;
+        self.efficiency_total += rating.efficiency;
+    }
+
+    /// Average efficiency over all finished games
+    pub fn average_efficiency(&self) -> u32 {
+        if self.games == 0 {
+            0
+        } else {
+            (self.efficiency_total as f64 / self.games as f64).round() as u32
+        }
+    }
+}
+
+/// Load the persisted per-user statistics
+pub(crate) fn load_user_stats(path: &Path) -> HashMap<String, UserStats> {
+    match fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Save the per-user statistics to the given path as pretty JSON
+pub(crate) fn save_user_stats(path: &Path, map: &HashMap<String, UserStats>) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(s) = serde_json::to_string_pretty(map)
+        && fs::write(path, s).is_ok()
+    {
+        crate::status::record_write(path);
+    }
+}
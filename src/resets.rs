@@ -0,0 +1,50 @@
+use crate::AppState;
+use std::collections::{BTreeSet, HashMap};
+
+/// Whose progression a reset clears
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetScope {
+    /// one user in one chat (`/reset_starts <user_id>`, `/reset_me`)
+    User { chat_id: i64, user_id: u64 },
+    /// every user of one chat (`/reset_starts chat`)
+    Chat(i64),
+    /// every user in every chat (`/reset_starts`, after confirmation)
+    All,
+}
+
+impl ResetScope {
+    /// Whether a "chat:user" key of the progression maps is in scope
+    fn covers(&self, key: &str) -> bool {
+        let Some((chat, user)) = key.rsplit_once(':') else {
+            return false;
+        };
+        match *self {
+            ResetScope::User { chat_id, user_id } => {
+                chat.parse() == Ok(chat_id) && user.parse() == Ok(user_id)
+            }
+            ResetScope::Chat(chat_id) => chat.parse() == Ok(chat_id),
+            ResetScope::All => true,
+        }
+    }
+}
+
+/// Clear the progression (start attempts, miss and win streaks) in `scope`,
+/// so the next games start again from `GAME_ATTEMPTS`. Statistics, season
+/// scores and running games are kept. Returns how many "chat:user" entries
+/// had something to reset.
+pub fn reset_progress(state: &mut AppState, scope: ResetScope) -> usize {
+    let mut affected = BTreeSet::new();
+    let mut clear = |map: &mut HashMap<String, i32>| {
+        map.retain(|k, _| {
+            let hit = scope.covers(k);
+            if hit {
+                affected.insert(k.clone());
+            }
+            !hit
+        });
+    };
+    clear(&mut state.user_start_attempts);
+    clear(&mut state.user_miss_streaks);
+    clear(&mut state.user_win_streaks);
+    affected.len()
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+/// What a user may do, from least to most powerful. A role includes every
+/// permission of the roles below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// granted per chat with `/ruoli aggiungi <user_id> moderatore`
+    Moderator,
+    /// Telegram administrator of the chat (or anyone in a private chat)
+    ChatAdmin,
+    /// granted by the owner with `/ruoli aggiungi <user_id> admin`
+    GlobalAdmin,
+    /// `BOT_OWNER_ID`
+    Owner,
+}
+
+/// The roles `/ruoli aggiungi|rimuovi` can grant, by their command keyword
+pub fn parse_grantable_role(s: &str) -> Option<Role> {
+    match s.to_lowercase().as_str() {
+        "moderatore" | "moderator" | "mod" => Some(Role::Moderator),
+        "admin" => Some(Role::GlobalAdmin),
+        _ => None,
+    }
+}
+
+/// A privileged action; each one needs a minimum role
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    /// `/reset_starts <user_id>`: reset one user's progression in the chat
+    ResetUser,
+    /// `/reset_starts chat`: reset the progression of the whole chat
+    ResetChat,
+    /// `/reset_starts`: reset everybody's progression in every chat
+    ResetStarts,
+    /// `/cancella_dati <user_id>`: erase another user's data
+    EraseOthersData,
+    /// `/reload_messages`
+    ReloadMessages,
+    /// `/audit`: read the audit log
+    ViewAudit,
+    /// `/manutenzione on|off`
+    Maintenance,
+    /// `/annuncio`: message every known chat
+    Broadcast,
+    /// `/status`: runtime diagnostics
+    ViewStatus,
+    /// `/modalita`, `/chat_consentite`: operating mode and allowed chats
+    ManageAccess,
+    /// `/lang chat <tag>`
+    SetChatLang,
+    /// `/imposta`: game rules of the current chat
+    ConfigureChat,
+    /// `/ban`, `/unban`, `/bannati` in the current chat
+    BanInChat,
+    /// `/ban ... globale` and friends: bans in every chat
+    BanGlobally,
+    /// grant or revoke moderators of the chat
+    ManageModerators,
+    /// grant or revoke global admins
+    ManageGlobalAdmins,
+}
+
+impl Permission {
+    /// The least powerful role allowed to do this
+    pub fn required_role(self) -> Role {
+        match self {
+            Permission::ResetUser => Role::Moderator,
+            Permission::ResetChat
+            | Permission::SetChatLang
+            | Permission::ConfigureChat
+            | Permission::BanInChat
+            | Permission::ManageModerators => Role::ChatAdmin,
+            Permission::ResetStarts | Permission::EraseOthersData | Permission::BanGlobally => {
+                Role::GlobalAdmin
+            }
+            Permission::ReloadMessages
+            | Permission::ViewAudit
+            | Permission::Maintenance
+            | Permission::Broadcast
+            | Permission::ViewStatus
+            | Permission::ManageAccess
+            | Permission::ManageGlobalAdmins => Role::Owner,
+        }
+    }
+
+    /// The permission needed to grant or revoke `role`
+    pub fn to_manage(role: Role) -> Permission {
+        if role >= Role::GlobalAdmin {
+            Permission::ManageGlobalAdmins
+        } else {
+            Permission::ManageModerators
+        }
+    }
+}
+
+/// Roles granted with `/ruoli`, persisted in `data/roles.json`. Owner and
+/// chat admins are not stored: they come from the configuration and from
+/// Telegram.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoleGrants {
+    pub global_admins: BTreeSet<u64>,
+    /// chat id -> moderators of that chat
+    pub moderators: BTreeMap<i64, BTreeSet<u64>>,
+}
+
+impl RoleGrants {
+    /// The highest granted role of a user in a chat
+    pub fn granted_role(&self, chat_id: i64, user_id: u64) -> Option<Role> {
+        if self.global_admins.contains(&user_id) {
+            Some(Role::GlobalAdmin)
+        } else if self
+            .moderators
+            .get(&chat_id)
+            .is_some_and(|m| m.contains(&user_id))
+        {
+            Some(Role::Moderator)
+        } else {
+            None
+        }
+    }
+
+    /// Grant a role; `false` if the user already had it. Only moderators
+    /// and global admins can be granted.
+    pub fn grant(&mut self, chat_id: i64, user_id: u64, role: Role) -> bool {
+        match role {
+            Role::GlobalAdmin => self.global_admins.insert(user_id),
+            Role::Moderator => self.moderators.entry(chat_id).or_default().insert(user_id),
+            Role::ChatAdmin | Role::Owner => false,
+        }
+    }
+
+    /// Revoke a role; `false` if the user did not have it
+    pub fn revoke(&mut self, chat_id: i64, user_id: u64, role: Role) -> bool {
+        match role {
+            Role::GlobalAdmin => self.global_admins.remove(&user_id),
+            Role::Moderator => {
+                let Some(mods) = self.moderators.get_mut(&chat_id) else {
+                    return false;
+                };
+                let removed = mods.remove(&user_id);
+                if mods.is_empty() {
+                    self.moderators.remove(&chat_id);
+                }
+                removed
+            }
+            Role::ChatAdmin | Role::Owner => false,
+        }
+    }
+
+    /// Chats in which a user is a moderator
+    pub fn moderated_chats(&self, user_id: u64) -> Vec<i64> {
+        self.moderators
+            .iter()
+            .filter(|(_, m)| m.contains(&user_id))
+            .map(|(c, _)| *c)
+            .collect()
+    }
+}
+
+/// Persisted role grants helpers
+pub(crate) fn load_role_grants(path: &Path) -> RoleGrants {
+    if !path.exists() {
+        return RoleGrants::default();
+    }
+    match fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str::<RoleGrants>(&s).unwrap_or_default(),
+        Err(_) => RoleGrants::default(),
+    }
+}
+
+/// Save the role grants to the given path as pretty JSON
+pub(crate) fn save_role_grants(path: &Path, grants: &RoleGrants) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(s) = serde_json::to_string_pretty(grants)
+        && fs::write(path, s).is_ok()
+    {
+        crate::status::record_write(path);
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, collections::HashSet, fs, path::Path};
+
+/// How long a season lasts before scores are archived and reset
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeasonPeriod {
+    Weekly,
+    Monthly,
+}
+
+/// Parse the `SEASON_PERIOD` value. Returns `None` for unknown values.
+pub fn parse_season_period(s: &str) -> Option<SeasonPeriod> {
+    match s.trim().to_lowercase().as_str() {
+        "weekly" | "week" | "settimanale" => Some(SeasonPeriod::Weekly),
+        "monthly" | "month" | "mensile" => Some(SeasonPeriod::Monthly),
+        _ => None,
+    }
+}
+
+/// Score of a single user in the current season
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeasonScore {
+    /// display name captured at the time of the last win
+    pub name: String,
+    pub wins: u32,
+    pub points: u32,
+}
+
+/// Current season: boundaries plus the live scores, persisted to
+/// `data/season.json`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SeasonState {
+    pub number: u32,
+    pub started_at: u64,
+    pub ends_at: u64,
+    // "chat:user" -> score, same composite key as the other persisted maps
+    pub scores: HashMap<String, SeasonScore>,
+    // chats where at least one game was played during this season
+    pub active_chats: HashSet<i64>,
+}
+
+/// Winner of a finished season in one chat
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Champion {
+    pub user_id: u64,
+    pub name: String,
+    pub wins: u32,
+    pub points: u32,
+}
+
+/// Archived season, appended to `data/season_archive.json` when it ends
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeasonRecord {
+    pub number: u32,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub champions: HashMap<i64, Champion>,
+    /// chats that had activity; every one of them gets the end-of-season announcement
+    #[serde(default)]
+    pub active_chats: Vec<i64>,
+}
+
+impl SeasonState {
+    /// Start season number 1 at `now`
+    pub fn new(period: SeasonPeriod, now: u64) -> Self {
+        SeasonState {
+            number: 1,
+            started_at: now,
+            ends_at: next_season_boundary(period, now),
+            scores: HashMap::new(),
+            active_chats: HashSet::new(),
+        }
+    }
+
+    /// Whether the season boundary has been reached
+    pub fn is_over(&self, now: u64) -> bool {
+        self.ends_at != 0 && now >= self.ends_at
+    }
+
+    /// Remember that a chat played during this season. Returns `true` the
+    /// first time the chat is seen so callers only persist on changes.
+    pub fn mark_active(&mut self, chat_id: i64) -> bool {
+        self.active_chats.insert(chat_id)
+    }
+
+    /// Add a win worth `points` to the user's score for this season
+    pub fn record_win(&mut self, chat_id: i64, user_id: u64, name: &str, points: u32) {
+        self.mark_active(chat_id);
+        let entry = self
+            .scores
+            .entry(format!("{}:{}", chat_id, user_id))
+            .or_default();
+        entry.name = name.to_string();
+        entry.wins += 1;
+        entry.points += points;
+    }
+
+    /// Standings of one chat, best first: points, then wins, then user id
+    /// so ties are stable.
+    pub fn standings(&self, chat_id: i64) -> Vec<(u64, SeasonScore)> {
+        let mut rows: Vec<(u64, SeasonScore)> = self
+            .scores
+            .iter()
+            .filter_map(|(k, v)| {
+                let (c, u) = split_composite(k)?;
+                (c == chat_id).then(|| (u, v.clone()))
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.1.points
+                .cmp(&a.1.points)
+                .then(b.1.wins.cmp(&a.1.wins))
+                .then(a.0.cmp(&b.0))
+        });
+        rows
+    }
+
+    /// Close the current season: build its archive record with the champion
+    /// of every chat, reset the scores and start the next season at `now`.
+    pub fn close(&mut self, period: SeasonPeriod, now: u64) -> SeasonRecord {
+        let mut chats: Vec<i64> = self.active_chats.iter().copied().collect();
+        chats.sort_unstable();
+        let mut champions = HashMap::new();
+        for chat_id in &chats {
+            if let Some((user_id, score)) = self.standings(*chat_id).into_iter().next() {
+                champions.insert(
+                    *chat_id,
+                    Champion {
+                        user_id,
+                        name: score.name,
+                        wins: score.wins,
+                        points: score.points,
+                    },
+                );
+            }
+        }
+        let record = SeasonRecord {
+            number: self.number,
+            started_at: self.started_at,
+            ended_at: now,
+            champions,
+            active_chats: chats,
+        };
+        self.number += 1;
+        self.started_at = now;
+        self.ends_at = next_season_boundary(period, now);
+        self.scores.clear();
+        self.active_chats.clear();
+        record
+    }
+}
+
+/// Split a "chat:user" composite key
+fn split_composite(key: &str) -> Option<(i64, u64)> {
+    let (c, u) = key.split_once(':')?;
+    Some((c.parse().ok()?, u.parse().ok()?))
+}
+
+/// Points awarded for a win: one for the win itself plus one for every
+/// attempt left unused.
+pub fn win_points(remaining_after_guess: i32) -> u32 {
+    1 + remaining_after_guess.max(0) as u32
+}
+
+const SECS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Unix timestamp of the next season boundary strictly after `now` (UTC):
+/// the next Monday 00:00 for weekly seasons, the first day of the next month
+/// 00:00 for monthly seasons.
+pub fn next_season_boundary(period: SeasonPeriod, now: u64) -> u64 {
+    let days = (now / SECS_PER_DAY) as i64;
+    let next_day = match period {
+        SeasonPeriod::Weekly => {
+            // 1970-01-01 was a Thursday; shift so Monday is 0
+            let weekday = (days + 3).rem_euclid(7);
+            days - weekday + 7
+        }
+        SeasonPeriod::Monthly => {
+            let (y, m, _) = civil_from_days(days);
+            if m == 12 {
+                days_from_civil(y + 1, 1, 1)
+            } else {
+                days_from_civil(y, m + 1, 1)
+            }
+        }
+    };
+    next_day as u64 * SECS_PER_DAY
+}
+
+/// Format a unix timestamp as a `YYYY-MM-DD` UTC date
+pub fn format_date(ts: u64) -> String {
+    let (y, m, d) = civil_from_days((ts / SECS_PER_DAY) as i64);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = m as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Proleptic Gregorian date for a number of days since 1970-01-01
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+/// Load the current season from disk; `None` when missing or unreadable
+pub(crate) fn load_season(path: &Path) -> Option<SeasonState> {
+    let s = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&s).ok()
+}
+
+/// Save the current season to the given path as pretty JSON
+pub(crate) fn save_season(path: &Path, season: &SeasonState) {
+    if let Some(json) = season_json(season) {
+        write_season(path, &json);
+    }
+}
+
+/// The current season as pretty JSON, to serialize it under the state lock
+/// and write it once the lock is released
+pub(crate) fn season_json(season: &SeasonState) -> Option<String> {
+    serde_json::to_string_pretty(season).ok()
+}
+
+/// Write a season serialized with [`season_json`]
+pub(crate) fn write_season(path: &Path, json: &str) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if fs::write(path, json).is_ok() {
+        crate::status::record_write(path);
+    }
+}
+
+/// Load the archive of past seasons
+pub(crate) fn load_season_archive(path: &Path) -> Vec<SeasonRecord> {
+    match fs::read_to_string(path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Save the archive of past seasons as pretty JSON
+pub(crate) fn save_season_archive(path: &Path, archive: &[SeasonRecord]) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(s) = serde_json::to_string_pretty(archive)
+        && fs::write(path, s).is_ok()
+    {
+        crate::status::record_write(path);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{AppState, Messages, bans::BanList};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+/// Last successful write of each data file in this run, unix timestamps.
+/// Global because the save helpers are plain functions called from
+/// everywhere; it is only read by `/status`.
+static LAST_WRITES: Mutex<BTreeMap<PathBuf, u64>> = Mutex::new(BTreeMap::new());
+
+/// Note that `path` was written successfully; called by the save helpers
+pub(crate) fn record_write(path: &Path) {
+    let mut writes = LAST_WRITES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    writes.insert(path.to_path_buf(), crate::now_unix());
+}
+
+/// When `path` was last written successfully since startup
+pub fn last_write(path: &Path) -> Option<u64> {
+    LAST_WRITES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(path)
+        .copied()
+}
+
+/// One file of the data directory, as shown by `/status`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataFile {
+    pub name: String,
+    pub size: u64,
+    /// last successful write: recorded by the save helpers in this run,
+    /// otherwise the file's modification time
+    pub written: Option<u64>,
+}
+
+/// The `*.json` and `*.jsonl` files of `dir`, by name
+pub fn data_files(dir: &Path) -> Vec<DataFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<DataFile> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "jsonl")
+        })
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            meta.is_file().then(|| DataFile {
+                name: e.file_name().to_string_lossy().into_owned(),
+                size: meta.len(),
+                written: last_write(&e.path()).or_else(|| {
+                    let modified = meta.modified().ok()?;
+                    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+                    Some(since_epoch.as_secs())
+                }),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    files
+}
+
+/// Window of the "recent" handler errors
+pub const RECENT_ERRORS_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Errors returned by the update handlers, counted in `run_bot`
+#[derive(Debug, Default)]
+pub struct HandlerErrors {
+    total: u64,
+    // times of the errors of the last RECENT_ERRORS_WINDOW
+    recent: VecDeque<Instant>,
+}
+
+impl HandlerErrors {
+    pub fn record(&mut self, now: Instant) {
+        self.total += 1;
+        self.forget_old(now);
+        self.recent.push_back(now);
+    }
+
+    /// Errors in the last [`RECENT_ERRORS_WINDOW`]
+    pub fn recent(&mut self, now: Instant) -> usize {
+        self.forget_old(now);
+        self.recent.len()
+    }
+
+    /// Errors since startup
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    fn forget_old(&mut self, now: Instant) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) >= RECENT_ERRORS_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+    }
+}
+
+/// Where the value of a setting comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// the process environment
+    Environment,
+    /// the `.env` file
+    DotEnv,
+    /// not set: the built-in default
+    Default,
+    /// set but rejected: the built-in default is used
+    Invalid,
+}
+
+/// The source of every setting read at startup, for `/status`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigSources {
+    // values defined in .env, to tell them from the process environment
+    dotenv: HashMap<String, String>,
+    pub settings: BTreeMap<&'static str, ConfigSource>,
+}
+
+impl ConfigSources {
+    /// `dotenv` holds the variables defined in `.env`; the process
+    /// environment wins over them
+    pub fn new(dotenv: HashMap<String, String>) -> Self {
+        ConfigSources {
+            dotenv,
+            settings: BTreeMap::new(),
+        }
+    }
+
+    /// Record the value `name` has in the environment and return it
+    pub fn record(&mut self, name: &'static str, value: Option<String>) -> Option<String> {
+        let source = match &value {
+            None => ConfigSource::Default,
+            Some(v) if self.dotenv.get(name) == Some(v) => ConfigSource::DotEnv,
+            Some(_) => ConfigSource::Environment,
+        };
+        self.settings.insert(name, source);
+        value
+    }
+
+    /// Read a variable of the environment, recording its source
+    pub fn var(&mut self, name: &'static str) -> Option<String> {
+        self.record(name, std::env::var(name).ok())
+    }
+
+    /// Read and parse a variable; a value that does not parse gives the
+    /// default and is recorded as invalid
+    pub fn parse<T: std::str::FromStr>(&mut self, name: &'static str, default: T) -> T {
+        match self.var(name) {
+            Some(v) => v.trim().parse().unwrap_or_else(|_| {
+                tracing::warn!("{} ({}) is not valid, using the default", name, v);
+                self.invalid(name);
+                default
+            }),
+            None => default,
+        }
+    }
+
+    /// Mark a setting whose value was rejected
+    pub fn invalid(&mut self, name: &'static str) {
+        self.settings.insert(name, ConfigSource::Invalid);
+    }
+}
+
+/// A duration in the words of `status_uptime`: days, hours and minutes,
+/// leaving out the leading units that are zero
+pub fn format_uptime(secs: u64, messages: &Messages) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    let span = if days > 0 {
+        "days"
+    } else if hours > 0 {
+        "hours"
+    } else {
+        "minutes"
+    };
+    messages.format(
+        &messages.status_uptime,
+        &[
+            ("span", span),
+            ("days", &messages.number(days)),
+            ("hours", &messages.number(hours)),
+            ("minutes", &messages.number(minutes)),
+        ],
+    )
+}
+
+/// A file size in the words of `status_size`: bytes under 1 KiB, otherwise
+/// KiB, MiB or GiB with one decimal
+pub fn format_size(bytes: u64, messages: &Messages) -> String {
+    const UNITS: [&str; 3] = ["kib", "mib", "gib"];
+    let (unit, tenths) = if bytes < 1024 {
+        ("b", bytes * 10)
+    } else {
+        let mut size = bytes as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        (UNITS[unit], (size * 10.0).round() as u64)
+    };
+    messages.format(
+        &messages.status_size,
+        &[
+            ("unit", unit),
+            ("whole", &messages.number(tenths / 10)),
+            ("tenths", &messages.number(tenths % 10)),
+        ],
+    )
+}
+
+/// Number of entries of each persisted map, named after its data file
+pub fn map_sizes(state: &AppState, bans: &BanList) -> Vec<(&'static str, usize)> {
+    let grants = &state.role_grants;
+    vec![
+        ("seen_welcome", state.seen_welcome.len()),
+        ("user_start_attempts", state.user_start_attempts.len()),
+        ("user_miss_streaks", state.user_miss_streaks.len()),
+        ("user_win_streaks", state.user_win_streaks.len()),
+        ("user_stats", state.user_stats.len()),
+        ("season", state.season.scores.len()),
+        ("season_archive", state.season_archive.len()),
+        ("chat_settings", state.chat_settings.len()),
+        (
+            "roles",
+            grants.global_admins.len() + grants.moderators.values().map(|m| m.len()).sum::<usize>(),
+        ),
+        (
+            "bans",
+            bans.global.len() + bans.chats.values().map(|b| b.len()).sum::<usize>(),
+        ),
+        ("audit_log", state.audit_log.len()),
+    ]
+}
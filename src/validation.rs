@@ -0,0 +1,147 @@
+use crate::plural::{matching_brace, parse_block};
+use crate::{Lang, Messages, default_messages, load_message_catalog};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// What is wrong with the placeholders of one message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IssueKind {
+    /// a placeholder of the English reference the translation does not use
+    Missing(String),
+    /// a placeholder the English reference does not have (usually a typo)
+    Unknown(String),
+    /// a `{` without its `}` or the other way round
+    UnbalancedBraces,
+}
+
+/// A placeholder problem in one message of one language
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlaceholderIssue {
+    pub lang: String,
+    pub key: String,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for PlaceholderIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            IssueKind::Missing(p) => write!(
+                f,
+                "{}.{}: missing placeholder {{{}}}",
+                self.lang, self.key, p
+            ),
+            IssueKind::Unknown(p) => write!(
+                f,
+                "{}.{}: unknown placeholder {{{}}}",
+                self.lang, self.key, p
+            ),
+            IssueKind::UnbalancedBraces => {
+                write!(f, "{}.{}: unbalanced braces", self.lang, self.key)
+            }
+        }
+    }
+}
+
+/// Placeholder names used by a template, including the keys of plural/select
+/// blocks and the placeholders inside their arms. `None` if braces are
+/// unbalanced.
+pub fn template_placeholders(template: &str) -> Option<BTreeSet<String>> {
+    let mut names = BTreeSet::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        if rest[..open].contains('}') {
+            return None;
+        }
+        let close = open + matching_brace(&rest[open..])?;
+        let inner = &rest[open + 1..close];
+        match parse_block(inner) {
+            Some(block) => {
+                names.insert(block.key.to_string());
+                for (_, arm) in block.arms {
+                    names.extend(template_placeholders(arm)?);
+                }
+            }
+            None => {
+                names.insert(inner.trim().to_string());
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    if rest.contains('}') {
+        return None;
+    }
+    Some(names)
+}
+
+/// Every message as `key -> template`, in a stable order
+fn templates(messages: &Messages) -> Vec<(String, String)> {
+    let value = serde_json::to_value(messages).unwrap_or_default();
+    let mut out: Vec<(String, String)> = value
+        .as_object()
+        .map(|o| {
+            o.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    out.sort_unstable();
+    out
+}
+
+/// Compare the placeholders of every message in every language with the
+/// built-in English reference. Issues are sorted by language and key.
+pub fn validate_placeholders(messages: &HashMap<String, Messages>) -> Vec<PlaceholderIssue> {
+    let reference: HashMap<String, BTreeSet<String>> = templates(&default_messages())
+        .into_iter()
+        .map(|(k, t)| (k, template_placeholders(&t).unwrap_or_default()))
+        .collect();
+    let mut tags: Vec<&String> = messages.keys().collect();
+    tags.sort_unstable();
+    let mut issues = Vec::new();
+    for tag in tags {
+        for (key, template) in templates(&messages[tag]) {
+            let issue = |kind| PlaceholderIssue {
+                lang: tag.clone(),
+                key: key.clone(),
+                kind,
+            };
+            let Some(found) = template_placeholders(&template) else {
+                issues.push(issue(IssueKind::UnbalancedBraces));
+                continue;
+            };
+            let expected = reference.get(&key).cloned().unwrap_or_default();
+            for p in expected.difference(&found) {
+                issues.push(issue(IssueKind::Missing(p.clone())));
+            }
+            for p in found.difference(&expected) {
+                issues.push(issue(IssueKind::Unknown(p.clone())));
+            }
+        }
+    }
+    issues
+}
+
+/// `check-messages [dir]` subcommand: report missing keys and placeholder
+/// problems of the message files, failing if any placeholder is wrong.
+pub fn check_messages(dir: &str) -> anyhow::Result<()> {
+    let catalog = load_message_catalog(dir, &Lang::en());
+    if catalog.messages.is_empty() {
+        anyhow::bail!("no message files found in {}", dir);
+    }
+    for (tag, keys) in &catalog.missing_keys {
+        println!("warning: {}/{}.json lacks {}", dir, tag, keys.join(", "));
+    }
+    let issues = validate_placeholders(&catalog.messages);
+    for issue in &issues {
+        println!("error: {}", issue);
+    }
+    if !issues.is_empty() {
+        anyhow::bail!("{} placeholder problem(s) in {}", issues.len(), dir);
+    }
+    println!(
+        "ok: {} language(s) checked in {}",
+        catalog.messages.len(),
+        dir
+    );
+    Ok(())
+}
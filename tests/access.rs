@@ -0,0 +1,119 @@
+use std::collections::BTreeSet;
+use telegram_bot_rust::access::{
+    AccessList, OperatingMode, Refusal, parse_allowed_chats, parse_operating_mode,
+};
+use telegram_bot_rust::roles::{Permission, Role};
+use telegram_bot_rust::{is_command, load_all_messages};
+
+#[test]
+fn modes_parse_in_english_and_italian() {
+    assert_eq!(parse_operating_mode("public"), Some(OperatingMode::Public));
+    assert_eq!(
+        parse_operating_mode("Lista"),
+        Some(OperatingMode::Allowlist)
+    );
+    assert_eq!(
+        parse_operating_mode("privato"),
+        Some(OperatingMode::PrivateOnly)
+    );
+    assert_eq!(parse_operating_mode("closed"), None);
+}
+
+#[test]
+fn allowlist_mode_serves_configured_and_added_chats() {
+    let configured = BTreeSet::from([-100]);
+    let mut access = AccessList {
+        mode: Some(OperatingMode::Allowlist),
+        ..Default::default()
+    };
+    access.chats.insert(-200);
+    let check = |chat, private| access.check(OperatingMode::Public, &configured, chat, private);
+    assert_eq!(check(-100, false), Ok(()));
+    assert_eq!(check(-200, false), Ok(()));
+    assert_eq!(check(-300, false), Err(Refusal::NotAllowed));
+    // private chats need to be listed too
+    assert_eq!(check(7, true), Err(Refusal::NotAllowed));
+}
+
+#[test]
+fn private_only_mode_refuses_groups() {
+    let access = AccessList::default();
+    let none = BTreeSet::new();
+    assert_eq!(
+        access.check(OperatingMode::PrivateOnly, &none, 7, true),
+        Ok(())
+    );
+    assert_eq!(
+        access.check(OperatingMode::PrivateOnly, &none, -100, false),
+        Err(Refusal::NotPrivate)
+    );
+    assert_eq!(
+        access.check(OperatingMode::Public, &none, -100, false),
+        Ok(())
+    );
+}
+
+#[test]
+fn the_runtime_mode_overrides_the_configured_one() {
+    let mut access = AccessList::default();
+    assert_eq!(
+        access.mode(OperatingMode::Allowlist),
+        OperatingMode::Allowlist
+    );
+    access.mode = Some(OperatingMode::Public);
+    assert_eq!(access.mode(OperatingMode::Allowlist), OperatingMode::Public);
+}
+
+#[test]
+fn allowed_chats_parse_from_a_comma_separated_list() {
+    assert_eq!(
+        parse_allowed_chats("-100, 7,,"),
+        Some(BTreeSet::from([-100, 7]))
+    );
+    assert_eq!(parse_allowed_chats(""), Some(BTreeSet::new()));
+    assert_eq!(parse_allowed_chats("-100,group"), None);
+}
+
+#[test]
+fn access_list_round_trips_as_json() {
+    let access = AccessList {
+        mode: Some(OperatingMode::PrivateOnly),
+        chats: BTreeSet::from([-100]),
+    };
+    let json = serde_json::to_string(&access).unwrap();
+    assert!(json.contains("\"private_only\""), "{}", json);
+    assert_eq!(serde_json::from_str::<AccessList>(&json).unwrap(), access);
+    assert_eq!(
+        serde_json::from_str::<AccessList>("{}").unwrap(),
+        AccessList::default()
+    );
+}
+
+#[test]
+fn access_is_managed_by_the_owner() {
+    assert_eq!(Permission::ManageAccess.required_role(), Role::Owner);
+    let messages = load_all_messages("messages");
+    let en = &messages["en"];
+    let reply = en.format(
+        &en.access_chat_changed,
+        &[("chat", "-100"), ("action", "add"), ("changed", "no")],
+    );
+    assert_eq!(reply, "Chat -100 was already allowed.");
+    let reply = en.format(
+        &en.access_mode_set,
+        &[("mode", "private_only"), ("changed", "yes")],
+    );
+    assert_eq!(reply, "Operating mode set: private chats only.");
+}
+
+#[test]
+fn access_commands_are_matched_as_whole_words() {
+    assert!(is_command("/modalita privato", "/modalita"));
+    assert!(is_command("/modalita@guess_bot", "/modalita"));
+    assert!(!is_command("/modalitax", "/modalita"));
+    assert!(is_command(
+        "/chat_consentite aggiungi -100",
+        "/chat_consentite"
+    ));
+    assert!(!is_command("/chat_consentite_tutte", "/chat_consentite"));
+}
@@ -0,0 +1,132 @@
+use std::time::{Duration, Instant};
+use telegram_bot_rust::alerts::{
+    Alert, AlertKey, ErrorAlerts, MAX_ALERTS_PER_HOUR, command_of, secs_until_next_digest,
+};
+use telegram_bot_rust::load_all_messages;
+
+fn key(command: &str, error: &str) -> AlertKey {
+    AlertKey {
+        command: command.to_string(),
+        error: error.to_string(),
+    }
+}
+
+#[test]
+fn repeats_are_suppressed_until_the_digest() {
+    let now = Instant::now();
+    let mut alerts = ErrorAlerts::default();
+    assert_eq!(
+        alerts.on_error(key("/gioco", "timeout"), now),
+        Alert::Notify
+    );
+    assert_eq!(
+        alerts.on_error(key("/gioco", "timeout"), now),
+        Alert::Suppress
+    );
+    assert_eq!(
+        alerts.on_error(key("/gioco", "timeout"), now),
+        Alert::Suppress
+    );
+    // another command or another error is a new notification
+    assert_eq!(
+        alerts.on_error(key("/stats", "timeout"), now),
+        Alert::Notify
+    );
+    assert_eq!(
+        alerts.on_error(key("/gioco", "blocked"), now),
+        Alert::Notify
+    );
+
+    let digest = alerts.take_digest();
+    assert_eq!(digest.len(), 1);
+    assert_eq!(digest[0].key, key("/gioco", "timeout"));
+    assert_eq!(digest[0].count, 2);
+    // a new period forwards the error again
+    assert_eq!(
+        alerts.on_error(key("/gioco", "timeout"), now),
+        Alert::Notify
+    );
+    assert!(alerts.take_digest().is_empty());
+}
+
+#[test]
+fn notifications_are_limited_per_hour() {
+    let now = Instant::now();
+    let mut alerts = ErrorAlerts::default();
+    for i in 0..MAX_ALERTS_PER_HOUR {
+        assert_eq!(
+            alerts.on_error(key("/gioco", &format!("error {}", i)), now),
+            Alert::Notify
+        );
+    }
+    assert_eq!(
+        alerts.on_error(key("/gioco", "one too many"), now),
+        Alert::Suppress
+    );
+    let later = now + Duration::from_secs(3600);
+    assert_eq!(
+        alerts.on_error(key("/gioco", "next hour"), later),
+        Alert::Notify
+    );
+    // the suppressed first occurrence is in the digest
+    let digest = alerts.take_digest();
+    assert_eq!(digest.len(), 1);
+    assert_eq!(digest[0].key.error, "one too many");
+    assert_eq!(digest[0].count, 1);
+}
+
+#[test]
+fn commands_drop_arguments_and_mentions() {
+    assert_eq!(
+        command_of("/Gioco@guess_bot now"),
+        Some("/gioco".to_string())
+    );
+    assert_eq!(command_of("42"), None);
+    assert_eq!(command_of("  "), None);
+}
+
+#[test]
+fn digests_are_sent_at_midnight_utc() {
+    assert_eq!(secs_until_next_digest(0), 86_400);
+    assert_eq!(secs_until_next_digest(86_400 - 60), 60);
+    assert_eq!(secs_until_next_digest(3 * 86_400 + 3600), 82_800);
+}
+
+#[test]
+fn error_alerts_name_the_chat_and_command() {
+    let messages = load_all_messages("messages");
+    let en = &messages["en"];
+    let alert = en.format(
+        &en.error_alert,
+        &[("chat", "-100"), ("command", "/gioco"), ("error", "boom")],
+    );
+    assert_eq!(
+        alert,
+        "⚠️ Handler error in chat -100\nCommand: /gioco\nboom"
+    );
+    // without a command alert and digest share the catalog wording
+    let it = &messages["it"];
+    let alert = it.format(
+        &it.error_alert,
+        &[
+            ("chat", "-100"),
+            ("command", &it.error_digest_no_command),
+            ("error", "boom"),
+        ],
+    );
+    assert!(alert.contains("Comando: nessun comando\n"), "{}", alert);
+    let line = it.format(
+        &it.error_digest_line,
+        &[
+            ("count", "3"),
+            ("command", &it.error_digest_no_command),
+            ("error", "boom"),
+        ],
+    );
+    assert_eq!(line, "• 3× nessun comando: boom");
+    let line = en.format(
+        &en.error_digest_line,
+        &[("count", "3"), ("command", "/gioco"), ("error", "boom")],
+    );
+    assert_eq!(line, "• 3× /gioco: boom");
+}
@@ -0,0 +1,98 @@
+use telegram_bot_rust::audit::{
+    AuditEntry, AuditOutcome, audit_page, format_timestamp, page_count,
+};
+use telegram_bot_rust::bans::BanList;
+use telegram_bot_rust::privacy::{erase_user_data, export_user_data};
+use telegram_bot_rust::{AppState, is_command};
+
+fn entries(n: u64) -> Vec<AuditEntry> {
+    (0..n)
+        .map(|i| AuditEntry::new(i, -100, Some(7), "/reset_starts chat", AuditOutcome::Done))
+        .collect()
+}
+
+#[test]
+fn entries_split_command_and_arguments() {
+    let e = AuditEntry::new(
+        0,
+        -100,
+        Some(7),
+        "  /Ruoli@GuessBot  aggiungi   8 moderatore ",
+        AuditOutcome::Denied,
+    );
+    assert_eq!(e.command, "/ruoli");
+    assert_eq!(e.args, "aggiungi 8 moderatore");
+    let e = AuditEntry::new(0, -100, None, "/reload_messages", AuditOutcome::Failed);
+    assert_eq!(e.command, "/reload_messages");
+    assert_eq!(e.args, "");
+}
+
+#[test]
+fn entries_round_trip_as_json_lines() {
+    let e = AuditEntry::new(
+        1_700_000_000,
+        -100,
+        None,
+        "/lang chat it",
+        AuditOutcome::Pending,
+    );
+    let line = serde_json::to_string(&e).unwrap();
+    assert!(!line.contains('\n'));
+    assert!(line.contains(r#""outcome":"pending""#), "{}", line);
+    assert_eq!(serde_json::from_str::<AuditEntry>(&line).unwrap(), e);
+}
+
+#[test]
+fn pages_are_newest_first() {
+    let log = entries(25);
+    assert_eq!(page_count(log.len(), 10), 3);
+    assert_eq!(page_count(0, 10), 1);
+    let first = audit_page(&log, 1, 10).unwrap();
+    assert_eq!(first.len(), 10);
+    assert_eq!(first[0].timestamp, 24);
+    let last = audit_page(&log, 3, 10).unwrap();
+    assert_eq!(
+        last.iter().map(|e| e.timestamp).collect::<Vec<_>>(),
+        vec![4, 3, 2, 1, 0]
+    );
+    assert!(audit_page(&log, 0, 10).is_none());
+    assert!(audit_page(&log, 4, 10).is_none());
+    // an empty log still has an (empty) first page
+    assert_eq!(audit_page(&[], 1, 10).unwrap().len(), 0);
+}
+
+#[test]
+fn timestamps_are_shown_in_utc() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+    assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13");
+}
+
+#[test]
+fn audit_entries_are_exported_and_survive_erasure() {
+    let mut state = AppState {
+        audit_log: entries(2),
+        ..Default::default()
+    };
+    state.audit_log.push(AuditEntry::new(
+        5,
+        -100,
+        Some(8),
+        "/reload_messages",
+        AuditOutcome::Denied,
+    ));
+    let export = export_user_data(&state, &BanList::default(), 7);
+    assert_eq!(export["audit_log"].as_array().unwrap().len(), 2);
+    erase_user_data(&mut state, 7);
+    assert_eq!(state.audit_log.len(), 3);
+}
+
+#[test]
+fn audit_command_is_matched_as_a_whole_word() {
+    assert!(is_command("/audit", "/audit"));
+    assert!(is_command("/audit 2", "/audit"));
+    assert!(is_command("/AUDIT@guess_bot 3", "/audit"));
+    assert!(!is_command("/auditing", "/audit"));
+    assert!(!is_command("/audit_log", "/audit"));
+    assert!(!is_command("audit", "/audit"));
+    assert!(!is_command("", "/audit"));
+}
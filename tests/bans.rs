@@ -0,0 +1,112 @@
+use telegram_bot_rust::bans::{Ban, BanList, BanScope, parse_ban_duration};
+use telegram_bot_rust::roles::{Permission, Role};
+use telegram_bot_rust::{is_command, load_all_messages};
+
+fn ban(until: Option<u64>) -> Ban {
+    Ban {
+        by: 1,
+        at: 100,
+        until,
+        reason: Some("spam".to_string()),
+    }
+}
+
+#[test]
+fn chat_bans_apply_to_one_chat_and_global_bans_everywhere() {
+    let mut bans = BanList::default();
+    bans.ban(BanScope::Chat(-100), 7, ban(None));
+    bans.ban(BanScope::Global, 8, ban(None));
+    assert!(bans.is_banned(-100, 7, 200));
+    assert!(!bans.is_banned(-200, 7, 200));
+    assert!(bans.is_banned(-100, 8, 200));
+    assert!(bans.is_banned(-200, 8, 200));
+    assert!(!bans.is_banned(-100, 9, 200));
+
+    assert!(bans.unban(BanScope::Chat(-100), 7));
+    assert!(!bans.unban(BanScope::Chat(-100), 7));
+    assert!(bans.chats.is_empty());
+    // a global ban is not lifted by a chat unban
+    assert!(!bans.unban(BanScope::Chat(-100), 8));
+    assert!(bans.is_banned(-100, 8, 200));
+}
+
+#[test]
+fn temporary_bans_expire() {
+    let mut bans = BanList::default();
+    bans.ban(BanScope::Chat(-100), 7, ban(Some(500)));
+    bans.ban(BanScope::Global, 8, ban(Some(1000)));
+    assert!(bans.is_banned(-100, 7, 499));
+    assert!(!bans.is_banned(-100, 7, 500));
+    assert_eq!(bans.active(BanScope::Chat(-100), 600).len(), 0);
+    assert_eq!(bans.active(BanScope::Global, 600).len(), 1);
+    assert_eq!(bans.remove_expired(600), 1);
+    assert!(bans.chats.is_empty());
+    assert_eq!(bans.global.len(), 1);
+}
+
+#[test]
+fn durations_need_a_unit() {
+    assert_eq!(parse_ban_duration("30m"), Some(30 * 60));
+    assert_eq!(parse_ban_duration("12H"), Some(12 * 60 * 60));
+    assert_eq!(parse_ban_duration("7d"), Some(7 * 24 * 60 * 60));
+    assert_eq!(parse_ban_duration("2w"), Some(14 * 24 * 60 * 60));
+    assert_eq!(parse_ban_duration("30"), None);
+    assert_eq!(parse_ban_duration("0d"), None);
+    assert_eq!(parse_ban_duration("spam"), None);
+    assert_eq!(parse_ban_duration("m"), None);
+    assert_eq!(parse_ban_duration("99999999999999999w"), None);
+}
+
+#[test]
+fn bans_round_trip_as_json() {
+    let mut bans = BanList::default();
+    bans.ban(BanScope::Chat(-100), 7, ban(Some(500)));
+    bans.ban(BanScope::Global, 8, ban(None));
+    let json = serde_json::to_string(&bans).unwrap();
+    assert_eq!(serde_json::from_str::<BanList>(&json).unwrap(), bans);
+    assert_eq!(
+        serde_json::from_str::<BanList>("{}").unwrap(),
+        BanList::default()
+    );
+}
+
+#[test]
+fn banning_needs_chat_admin_or_global_admin() {
+    assert_eq!(Permission::BanInChat.required_role(), Role::ChatAdmin);
+    assert_eq!(Permission::BanGlobally.required_role(), Role::GlobalAdmin);
+}
+
+#[test]
+fn ban_messages_select_scope_and_expiry() {
+    let messages = load_all_messages("messages");
+    let en = &messages["en"];
+    let done = en.format(
+        &en.ban_done,
+        &[("user", "7"), ("scope", "chat"), ("until", "never")],
+    );
+    assert!(done.contains("from this chat permanently"), "{}", done);
+    let done = en.format(
+        &en.ban_done,
+        &[
+            ("user", "7"),
+            ("scope", "global"),
+            ("until", "2026-10-20 12:00"),
+        ],
+    );
+    assert!(
+        done.contains("from every chat until 2026-10-20 12:00 UTC"),
+        "{}",
+        done
+    );
+}
+
+#[test]
+fn ban_commands_are_matched_as_whole_words() {
+    assert!(is_command("/ban 42 globale 7d spam", "/ban"));
+    assert!(is_command("/ban@guess_bot", "/ban"));
+    assert!(is_command("/bannati globale", "/bannati"));
+    assert!(!is_command("/bannati", "/ban"));
+    assert!(!is_command("/banana", "/ban"));
+    assert!(!is_command("/bando 3", "/ban"));
+    assert!(!is_command("/unbanned", "/unban"));
+}
@@ -0,0 +1,98 @@
+use telegram_bot_rust::bidi::{FSI, LRI, PDI, is_rtl, isolate, isolate_commands};
+use telegram_bot_rust::seasons::{SeasonPeriod, SeasonState};
+use telegram_bot_rust::{load_all_messages, season_report};
+
+fn fsi(s: &str) -> String {
+    format!("{}{}{}", FSI, s, PDI)
+}
+
+fn lri(s: &str) -> String {
+    format!("{}{}{}", LRI, s, PDI)
+}
+
+#[test]
+fn rtl_detection_uses_script_then_language() {
+    assert!(is_rtl("ar"));
+    assert!(is_rtl("ar-EG"));
+    assert!(is_rtl("he"));
+    assert!(is_rtl("fa"));
+    assert!(is_rtl("pa-Arab"));
+    assert!(!is_rtl("en"));
+    assert!(!is_rtl("zh-Hant"));
+    assert!(!is_rtl("az-Latn"));
+    assert!(!is_rtl("uz-Latn"));
+}
+
+#[test]
+fn isolate_wraps_each_line() {
+    assert_eq!(isolate("Ahmed"), fsi("Ahmed"));
+    assert_eq!(isolate("a\n\nb"), format!("{}\n\n{}", fsi("a"), fsi("b")));
+    assert_eq!(isolate(""), "");
+}
+
+#[test]
+fn commands_are_isolated_but_urls_and_fractions_are_not() {
+    assert_eq!(
+        isolate_commands("استخدم /gioco الآن"),
+        format!("استخدم {} الآن", lri("/gioco"))
+    );
+    assert_eq!(
+        isolate_commands("(/cancella_dati conferma)"),
+        format!("({} conferma)", lri("/cancella_dati"))
+    );
+    assert_eq!(isolate_commands("https://t.me/bot"), "https://t.me/bot");
+    assert_eq!(isolate_commands("1/2 و a/b"), "1/2 و a/b");
+    let once = isolate_commands("/lang");
+    assert_eq!(isolate_commands(&once), once);
+}
+
+#[test]
+fn arabic_templates_isolate_names_numbers_and_commands() {
+    let all = load_all_messages("messages");
+    let ar = &all["ar"];
+
+    let welcome = ar.format(
+        &ar.welcome_prompt,
+        &[("name", "John"), ("lang_commands", "/lang en")],
+    );
+    assert!(welcome.contains(&fsi("John")));
+    assert!(welcome.contains(&lri("/gioco")));
+    assert!(welcome.contains(&lri("/config")));
+    assert!(!welcome.contains('{'));
+
+    // `#` inside a plural arm is isolated like any other value
+    let low = ar.format(&ar.too_low, &[("attempts", "5")]);
+    assert!(low.contains(&fsi("5")), "{:?}", low);
+
+    let started = ar.format(
+        &ar.game_started,
+        &[("min", "1"), ("max", "100"), ("attempts", "7")],
+    );
+    assert!(started.contains(&fsi("1")));
+    assert!(started.contains(&fsi("100")));
+
+    assert!(ar.no_attempts.contains(&lri("/gioco")));
+}
+
+#[test]
+fn ltr_templates_are_untouched() {
+    let all = load_all_messages("messages");
+    let en = &all["en"];
+    let welcome = en.format(
+        &en.welcome_prompt,
+        &[("name", "أحمد"), ("lang_commands", "/lang ar")],
+    );
+    assert!(welcome.starts_with("Hi أحمد!"));
+    assert!(!welcome.contains([FSI, LRI, PDI]));
+    assert!(!en.no_attempts.contains(LRI));
+}
+
+#[test]
+fn arabic_season_report_isolates_player_names() {
+    let all = load_all_messages("messages");
+    let ar = &all["ar"];
+    let mut season = SeasonState::new(SeasonPeriod::Weekly, 0);
+    season.record_win(-1, 7, "Alice", 3);
+    let report = season_report(&season, &[], -1, ar);
+    assert!(report.contains(&fsi("Alice")), "{:?}", report);
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use telegram_bot_rust::broadcast::{Announcement, known_chats};
+use telegram_bot_rust::roles::{Permission, Role};
+use telegram_bot_rust::{AppState, GameState, is_command, load_all_messages, parse_lang};
+
+#[test]
+fn announcements_pick_the_chat_language_or_the_untagged_text() {
+    let announcement = Announcement::parse(
+        "Back soon\nafter the update\n[it] Torniamo presto\n[ru] Скоро вернёмся",
+    )
+    .unwrap();
+    let aliases = HashMap::new();
+    let text_for = |tag: &str| announcement.text_for(&parse_lang(tag).unwrap(), &aliases);
+    assert_eq!(text_for("en"), "Back soon\nafter the update");
+    assert_eq!(text_for("it"), "Torniamo presto");
+    // regional variants fall back to their base language
+    assert_eq!(text_for("it-CH"), "Torniamo presto");
+    assert_eq!(text_for("ru"), "Скоро вернёмся");
+    assert_eq!(text_for("zh"), "Back soon\nafter the update");
+}
+
+#[test]
+fn announcements_without_untagged_text_fall_back_to_the_first_variant() {
+    let announcement = Announcement::parse("[it] Torniamo presto\n[en] Back soon").unwrap();
+    let aliases = HashMap::new();
+    assert_eq!(
+        announcement.text_for(&parse_lang("zh").unwrap(), &aliases),
+        "Torniamo presto"
+    );
+    assert_eq!(Announcement::parse(""), None);
+    assert_eq!(Announcement::parse("  \n[it]  "), None);
+    // a marker that is not a language tag is rejected, not sent as text
+    assert_eq!(Announcement::parse("[??] hi"), None);
+}
+
+#[test]
+fn known_chats_cover_every_stored_chat() {
+    let mut state = AppState::default();
+    state.by_user.insert((-100, 7), GameState::default());
+    state
+        .user_langs
+        .insert((-200, 7), parse_lang("it").unwrap());
+    state.seen_welcome.insert("-300:7".to_string(), 1);
+    state
+        .user_stats
+        .insert("8:8".to_string(), Default::default());
+    state.chat_settings.insert(-400, Default::default());
+    state
+        .role_grants
+        .moderators
+        .entry(-500)
+        .or_default()
+        .insert(9);
+    let chats: Vec<i64> = known_chats(&state).into_iter().collect();
+    assert_eq!(chats, vec![-500, -400, -300, -200, -100, 8]);
+}
+
+#[test]
+fn maintenance_and_broadcasts_are_for_the_owner() {
+    assert_eq!(Permission::Maintenance.required_role(), Role::Owner);
+    assert_eq!(Permission::Broadcast.required_role(), Role::Owner);
+}
+
+#[test]
+fn broadcast_report_counts_deliveries_and_failures() {
+    let messages = load_all_messages("messages");
+    let en = &messages["en"];
+    let done = en.format(
+        &en.broadcast_done,
+        &[("sent", "2"), ("count", "3"), ("failed", "1")],
+    );
+    assert_eq!(
+        done,
+        "📢 Announcement delivered to 2 of 3 chats; 1 failure."
+    );
+    let started = en.format(&en.broadcast_started, &[("count", "1")]);
+    assert!(started.contains("1 chat…"), "{}", started);
+}
+
+#[test]
+fn owner_commands_are_matched_as_whole_words() {
+    assert!(is_command("/annuncio ciao a tutti", "/annuncio"));
+    assert!(is_command("/annuncio@guess_bot ciao", "/annuncio"));
+    assert!(!is_command("/annunciox ciao", "/annuncio"));
+    assert!(is_command("/manutenzione on", "/manutenzione"));
+    assert!(!is_command("/manutenzionefoo", "/manutenzione"));
+}
@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use telegram_bot_rust::chat_settings::{
+    self, AdminCache, CHAT_ADMINS_TTL_SECS, ChatSettings, GameRules, RulesError,
+};
+use telegram_bot_rust::{
+    AppState, Config, chat_rules, effective_lang, is_command, load_all_messages, parse_lang,
+};
+use tokio::sync::RwLock;
+
+#[test]
+fn admin_cache_expires_after_ttl() {
+    let mut cache = AdminCache::default();
+    assert!(cache.get(-100, 1_000).is_none());
+    cache.insert(-100, HashSet::from([7, 8]), 1_000);
+    assert!(cache.get(-100, 1_000).unwrap().contains(&7));
+    assert!(cache.get(-100, 1_000 + CHAT_ADMINS_TTL_SECS - 1).is_some());
+    assert!(cache.get(-100, 1_000 + CHAT_ADMINS_TTL_SECS).is_none());
+    assert!(cache.get(-200, 1_000).is_none());
+}
+
+#[test]
+fn chat_settings_round_trip_as_json() {
+    let mut map = HashMap::new();
+    map.insert(
+        -100i64,
+        ChatSettings {
+            lang: parse_lang("pt_br"),
+            ..Default::default()
+        },
+    );
+    map.insert(-200i64, ChatSettings::default());
+    let json = serde_json::to_string(&map).unwrap();
+    assert!(json.contains("\"pt-BR\""));
+    let back: HashMap<i64, ChatSettings> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, map);
+    // settings written by a future version with unknown fields still load
+    let extra: ChatSettings = serde_json::from_str(r#"{"lang": "it", "other": 1}"#).unwrap();
+    assert_eq!(extra.lang, parse_lang("it"));
+    assert!(serde_json::from_str::<ChatSettings>(r#"{"lang": "not a tag"}"#).is_err());
+}
+
+#[tokio::test]
+async fn chat_language_applies_to_members_without_a_preference() {
+    let mut state = AppState::default();
+    state.chat_settings.insert(
+        -100,
+        ChatSettings {
+            lang: parse_lang("ru"),
+            ..Default::default()
+        },
+    );
+    state
+        .user_langs
+        .insert((-100, 7), parse_lang("it").unwrap());
+    let state = Arc::new(RwLock::new(state));
+    let config = Config {
+        messages: load_all_messages("messages").into(),
+        ..Default::default()
+    };
+
+    let member = effective_lang(&state, &config, -100, Some(8), Some("en")).await;
+    assert_eq!(member, parse_lang("ru").unwrap());
+    // a personal preference still wins over the chat setting
+    let own = effective_lang(&state, &config, -100, Some(7), Some("en")).await;
+    assert_eq!(own, parse_lang("it").unwrap());
+}
+
+#[test]
+fn game_rules_are_overridden_per_chat() {
+    let config = Config::default();
+    let settings = chat_settings::apply_rule_args(
+        &ChatSettings::default(),
+        &["min", "1", "MAX", "500", "tentativi", "9"],
+    )
+    .unwrap();
+    let rules = settings.game_rules(config.game_rules());
+    assert_eq!(
+        rules,
+        GameRules {
+            min: 1,
+            max: 500,
+            attempts: 9,
+            restart_threshold: config.restart_threshold,
+        }
+    );
+    assert!(settings.has_game_overrides());
+
+    let mut state = AppState::default();
+    state.chat_settings.insert(-100, settings);
+    assert_eq!(chat_rules(&state, &config, -100), rules);
+    assert_eq!(chat_rules(&state, &config, -200), config.game_rules());
+
+    // malformed arguments are rejected as a whole
+    for args in [&["min"][..], &["min", "x"], &["colore", "3"], &[]] {
+        assert!(
+            chat_settings::apply_rule_args(&ChatSettings::default(), args).is_none(),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn overrides_follow_the_startup_validation() {
+    let base = Config::default().game_rules();
+    let invalid = |args: &[&str]| {
+        let s = chat_settings::apply_rule_args(&ChatSettings::default(), args).unwrap();
+        s.game_rules(base).validate().unwrap_err()
+    };
+    assert_eq!(
+        invalid(&["min", "200"]),
+        RulesError::MinNotBelowMax { min: 200, max: 100 }
+    );
+    assert_eq!(
+        invalid(&["tentativi", "0"]),
+        RulesError::AttemptsNotPositive(0)
+    );
+    assert_eq!(
+        invalid(&["soglia", "-1"]),
+        RulesError::NegativeThreshold(-1)
+    );
+    // the startup error text is unchanged
+    assert_eq!(
+        RulesError::MinNotBelowMax { min: 5, max: 5 }.to_string(),
+        "GAME_MIN (5) must be less than GAME_MAX (5)."
+    );
+}
+
+#[test]
+fn overrides_are_persisted_only_when_set() {
+    let mut settings = ChatSettings {
+        attempts: Some(7),
+        ..Default::default()
+    };
+    let json = serde_json::to_string(&settings).unwrap();
+    assert_eq!(json, r#"{"attempts":7}"#);
+    assert_eq!(
+        serde_json::from_str::<ChatSettings>(&json).unwrap(),
+        settings
+    );
+    settings.clear_game_overrides();
+    assert!(settings.is_empty());
+}
+
+#[test]
+fn imposta_is_matched_as_a_whole_command() {
+    assert!(is_command("/imposta min 1", "/imposta"));
+    assert!(is_command("/imposta@guess_bot", "/imposta"));
+    assert!(!is_command("/impostazioni", "/imposta"));
+}
@@ -0,0 +1,11 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// A fresh, empty directory for the message files of one test; `name` keeps
+/// the tests of a file apart, the process id the test binaries
+pub fn temp_messages_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("messages_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
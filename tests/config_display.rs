@@ -11,6 +11,7 @@ async fn config_shows_active_attempts_and_remaining_number_attempts() {
         target: 42,
         attempts_left: 5,
         start_attempts: 10,
+        ..Default::default()
     };
     let chat_id = 123i64;
     let user_id = 456u64;
@@ -23,16 +24,16 @@ async fn config_shows_active_attempts_and_remaining_number_attempts() {
     let state = Arc::new(RwLock::new(AppState {
         by_user,
         user_langs: HashMap::new(),
-        chat_langs: HashMap::new(),
         seen_welcome: HashMap::new(),
         user_start_attempts: HashMap::new(),
         user_miss_streaks,
+        ..Default::default()
     }));
 
     // Build a config with restart_threshold = 3
     let mut messages = HashMap::new();
     // Ensure the config template contains the {number_attempts} placeholder
-    let mut msgs = default_messages(Lang::En);
+    let mut msgs = default_messages();
     msgs.config = "Current configuration: min = {min}, max = {max}, attempts = {attempts}, number_attempts = {number_attempts}".to_string();
     messages.insert("it".to_string(), msgs);
 
@@ -41,11 +42,18 @@ async fn config_shows_active_attempts_and_remaining_number_attempts() {
         max: 100,
         attempts: 10,
         restart_threshold: 3,
-        lang: Lang::It,
-        messages,
+        lang: parse_lang("it").unwrap(),
+        messages: messages.into(),
         ttl_seconds: 60 * 60 * 24,
         bot_owner_id: None,
         reset_user_starts: HashSet::new(),
+        season_period: None,
+        lang_aliases: HashMap::new(),
+        rate_limits: Default::default(),
+        config_sources: Default::default(),
+        operating_mode: Default::default(),
+        allowed_chats: Default::default(),
+        leave_refused_chats: false,
     };
     let shared_cfg = Arc::new(cfg);
 
@@ -89,6 +97,7 @@ async fn config_includes_next_attempts_value() {
         target: 11,
         attempts_left: 3,
         start_attempts: 7,
+        ..Default::default()
     };
     let chat_id = 33i64;
     let user_id = 44u64;
@@ -97,15 +106,15 @@ async fn config_includes_next_attempts_value() {
     let state = Arc::new(RwLock::new(AppState {
         by_user,
         user_langs: HashMap::new(),
-        chat_langs: HashMap::new(),
         seen_welcome: HashMap::new(),
         user_start_attempts: HashMap::new(),
         user_miss_streaks: HashMap::new(),
+        ..Default::default()
     }));
 
     // Use default messages but ensure config template contains next_attempts
     let mut messages = HashMap::new();
-    let mut msgs = default_messages(Lang::En);
+    let mut msgs = default_messages();
     msgs.config = "Current configuration: min = {min}, max = {max}, attempts = {attempts}, number_attempts = {number_attempts}, next_attempts = {next_attempts}".to_string();
     messages.insert("it".to_string(), msgs);
 
@@ -114,11 +123,18 @@ async fn config_includes_next_attempts_value() {
         max: 50,
         attempts: 10,
         restart_threshold: 3,
-        lang: Lang::It,
-        messages,
+        lang: parse_lang("it").unwrap(),
+        messages: messages.into(),
         ttl_seconds: 60 * 60 * 24,
         bot_owner_id: None,
         reset_user_starts: HashSet::new(),
+        season_period: None,
+        lang_aliases: HashMap::new(),
+        rate_limits: Default::default(),
+        config_sources: Default::default(),
+        operating_mode: Default::default(),
+        allowed_chats: Default::default(),
+        leave_refused_chats: false,
     };
     let shared_cfg = Arc::new(cfg);
 
@@ -168,6 +184,7 @@ async fn config_uses_real_italian_messages_file() {
         target: 99,
         attempts_left: 5,
         start_attempts: 10,
+        ..Default::default()
     };
     let chat_id = 10i64;
     let user_id = 20u64;
@@ -180,10 +197,10 @@ async fn config_uses_real_italian_messages_file() {
     let state = Arc::new(RwLock::new(AppState {
         by_user,
         user_langs: HashMap::new(),
-        chat_langs: HashMap::new(),
         seen_welcome: HashMap::new(),
         user_start_attempts: HashMap::new(),
         user_miss_streaks,
+        ..Default::default()
     }));
 
     let mut messages_map = HashMap::new();
@@ -194,11 +211,18 @@ async fn config_uses_real_italian_messages_file() {
         max: 100,
         attempts: 10,
         restart_threshold: 3,
-        lang: Lang::It,
-        messages: messages_map,
+        lang: parse_lang("it").unwrap(),
+        messages: messages_map.into(),
         ttl_seconds: 60 * 60 * 24,
         bot_owner_id: None,
         reset_user_starts: HashSet::new(),
+        season_period: None,
+        lang_aliases: HashMap::new(),
+        rate_limits: Default::default(),
+        config_sources: Default::default(),
+        operating_mode: Default::default(),
+        allowed_chats: Default::default(),
+        leave_refused_chats: false,
     };
     let shared_cfg = Arc::new(cfg);
 
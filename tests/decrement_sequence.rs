@@ -1,14 +1,14 @@
-use telegram_bot_rust::next_attempts_after_win;
+use telegram_bot_rust::{
+    next_attempts_for_win_streak, remaining_decrements, win_streak_after_game,
+};
 
 #[test]
 fn consecutive_quick_wins_decrement_start_attempts() {
     let mut start = 10;
     let threshold = 3;
-    // simulate three consecutive wins where the user used 1 attempt each time
-    for expected in &[9, 8, 7] {
-        // remaining_after_guess = start - attempts_used (attempts_used=1)
-        let remaining_after_guess = start - 1;
-        let next = next_attempts_after_win(start, remaining_after_guess, threshold);
+    // simulate three consecutive wins: each one is still within the cap
+    for (win_streak, expected) in [9, 8, 7].iter().enumerate() {
+        let next = next_attempts_for_win_streak(start, win_streak as i32, threshold);
         assert_eq!(next, *expected, "start {} -> next {}", start, next);
         start = next;
     }
@@ -18,10 +18,71 @@ fn consecutive_quick_wins_decrement_start_attempts() {
 fn any_win_decrements_by_one() {
     let start = 10;
     let threshold = 3;
-    // Under the new behavior every win reduces the next start attempts by 1,
-    // regardless of how many attempts were used. remaining_after_guess here
-    // represents a "slow" win (used 4 attempts).
-    let remaining_after_guess = start - 4; // used = 4
-    let next = next_attempts_after_win(start, remaining_after_guess, threshold);
+    // how many attempts the win used does not matter, only the win streak
+    let next = next_attempts_for_win_streak(start, 0, threshold);
     assert_eq!(next, 9);
 }
+
+#[test]
+fn decrements_stop_after_number_attempts_consecutive_wins() {
+    let threshold = 3;
+    let mut start = 10;
+    let mut win_streak = 0;
+    let mut sequence = Vec::new();
+    // five wins in a row: only the first three decrement (10 -> 9 -> 8 -> 7)
+    for _ in 0..5 {
+        start = next_attempts_for_win_streak(start, win_streak, threshold);
+        win_streak = std::cmp::min(win_streak + 1, threshold);
+        sequence.push(start);
+    }
+    assert_eq!(sequence, vec![9, 8, 7, 7, 7]);
+    assert_eq!(win_streak, threshold);
+
+    // after the reset condition the win streak starts over and decrements resume
+    let start = next_attempts_for_win_streak(10, 0, threshold);
+    assert_eq!(start, 9);
+}
+
+#[test]
+fn remaining_decrements_reflect_the_win_streak() {
+    let threshold = 3;
+    assert_eq!(remaining_decrements(1, threshold), 2);
+    assert_eq!(remaining_decrements(2, threshold), 1);
+    assert_eq!(remaining_decrements(3, threshold), 0);
+    // never negative, even with a lowered NUMBER_ATTEMPTS and an old streak
+    assert_eq!(remaining_decrements(3, 2), 0);
+}
+
+#[test]
+fn zero_threshold_disables_decrements_and_floor_is_one() {
+    assert_eq!(next_attempts_for_win_streak(5, 0, 0), 5);
+    assert_eq!(next_attempts_for_win_streak(1, 0, 3), 1);
+}
+
+#[test]
+fn a_loss_below_the_reset_threshold_keeps_decrements_paused() {
+    let threshold = 3;
+    let attempts = 10;
+    let mut start = attempts;
+    let mut win_streak = 0;
+    let mut miss_streak = 0;
+    let mut sequence = Vec::new();
+    // three wins reach the cap, a single loss does not resume the
+    // decrements, three losses in a row reset and the next win decrements
+    for won in [true, true, true, false, true, false, false, false, true] {
+        if won {
+            start = next_attempts_for_win_streak(start, win_streak, threshold);
+            miss_streak = 0;
+        } else {
+            miss_streak += 1;
+        }
+        win_streak = win_streak_after_game(won, win_streak, miss_streak, threshold);
+        if miss_streak >= threshold {
+            start = attempts;
+            miss_streak = 0;
+        }
+        sequence.push(start);
+    }
+    assert_eq!(sequence, vec![9, 8, 7, 7, 7, 7, 7, 10, 9]);
+    assert_eq!(win_streak, 1);
+}
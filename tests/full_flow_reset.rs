@@ -1,4 +1,4 @@
-use telegram_bot_rust::{GameState, next_attempts_after_win};
+use telegram_bot_rust::{GameState, next_attempts_for_win_streak};
 
 #[test]
 fn simulate_win_resets_and_decrements_start_attempts() {
@@ -7,18 +7,20 @@ fn simulate_win_resets_and_decrements_start_attempts() {
         target: 42,
         attempts_left: 10,
         start_attempts: 10,
+        ..Default::default()
     };
     // simulate a winning guess on the first try: decrement then check
     game.attempts_left = game.attempts_left.saturating_sub(1); // now 9
     assert_eq!(game.attempts_left, 9);
-    // compute next attempts using start_attempts
-    let next = next_attempts_after_win(game.start_attempts, game.attempts_left, 3);
+    // compute next attempts using start_attempts; first win of the streak
+    let next = next_attempts_for_win_streak(game.start_attempts, 0, 3);
     assert_eq!(next, 9, "expected start attempts to decrement from 10 to 9");
     // reset the game as the handler would
     game = GameState {
         target: 7,
         attempts_left: next,
         start_attempts: next,
+        ..Default::default()
     };
     assert_eq!(game.start_attempts, 9);
 }
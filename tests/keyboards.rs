@@ -0,0 +1,119 @@
+use telegram_bot_rust::GameState;
+use telegram_bot_rust::keyboards::{
+    CallbackAction, KEYPAD_MAX_LEN, KeypadKey, apply_keypad_key, keypad_keyboard,
+    language_keyboard, parse_callback_data, play_again_keyboard,
+};
+use teloxide::types::{InlineKeyboardButtonKind, InlineKeyboardMarkup};
+
+fn callback_data(markup: &InlineKeyboardMarkup) -> Vec<String> {
+    markup
+        .inline_keyboard
+        .iter()
+        .flatten()
+        .filter_map(|b| match &b.kind {
+            InlineKeyboardButtonKind::CallbackData(d) => Some(d.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn keyboards_round_trip_through_the_parser() {
+    let again = callback_data(&play_again_keyboard("Play again", 7, 42));
+    assert_eq!(
+        parse_callback_data(&again[0]),
+        Some(CallbackAction::PlayAgain {
+            user_id: 7,
+            game_id: 42
+        })
+    );
+
+    let langs = language_keyboard(&[
+        ("en".to_string(), "English".to_string()),
+        ("it".to_string(), "Italiano".to_string()),
+    ]);
+    assert_eq!(
+        parse_callback_data(&callback_data(&langs)[1]),
+        Some(CallbackAction::SetLang {
+            tag: "it".to_string()
+        })
+    );
+
+    let keypad = callback_data(&keypad_keyboard(7, u64::MAX));
+    // ten digits, sign, backspace and submit
+    assert_eq!(keypad.len(), 13);
+    for data in &keypad {
+        // Telegram rejects callback data longer than 64 bytes
+        assert!(data.len() <= 64, "{}", data);
+        match parse_callback_data(data) {
+            Some(CallbackAction::Keypad {
+                user_id, game_id, ..
+            }) => assert_eq!((user_id, game_id), (7, u64::MAX)),
+            other => panic!("unexpected {:?} for {}", other, data),
+        }
+    }
+}
+
+#[test]
+fn malformed_callback_data_is_rejected() {
+    for data in [
+        "",
+        "again",
+        "again:7",
+        "again:x:1",
+        "again:7:1:extra",
+        "lang:",
+        "kp:7:1:12",
+        "kp:7:1:x",
+        "unknown:1",
+    ] {
+        assert_eq!(parse_callback_data(data), None, "{}", data);
+    }
+}
+
+#[test]
+fn keypad_composes_a_guess() {
+    let mut input = String::new();
+    for key in [
+        KeypadKey::Digit(4),
+        KeypadKey::Digit(2),
+        KeypadKey::Digit(9),
+        KeypadKey::Backspace,
+        KeypadKey::Sign,
+    ] {
+        input = apply_keypad_key(&input, key);
+    }
+    assert_eq!(input, "-42");
+    assert_eq!(apply_keypad_key(&input, KeypadKey::Sign), "42");
+    assert_eq!(apply_keypad_key(&input, KeypadKey::Submit), "-42");
+    assert_eq!(apply_keypad_key("", KeypadKey::Backspace), "");
+
+    let full = "9".repeat(KEYPAD_MAX_LEN);
+    assert_eq!(apply_keypad_key(&full, KeypadKey::Digit(1)), full);
+}
+
+#[test]
+fn play_again_after_a_win_never_discards_a_game_in_progress() {
+    // the user wins: the button is bound to the finished game and a new
+    // game replaces it right away
+    let won = GameState::new(1, 100, 10);
+    let button = play_again_keyboard("again", 7, won.id);
+    let Some(CallbackAction::PlayAgain { game_id, .. }) =
+        parse_callback_data(&callback_data(&button)[0])
+    else {
+        panic!("not a play again button");
+    };
+    let mut next = GameState::new(1, 100, 9);
+    // nothing played yet: restarting loses nothing
+    assert!(next.accepts_play_again(game_id));
+
+    // one guess in the new game: the old button is stale
+    next.record_guess(50);
+    assert!(!next.accepts_play_again(game_id));
+
+    // after a loss the finished game stays current and accepts its button
+    let mut lost = GameState::new(1, 100, 1);
+    lost.record_guess(lost.target + 1);
+    lost.attempts_left = 0;
+    assert!(lost.accepts_play_again(lost.id));
+}
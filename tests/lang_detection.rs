@@ -1,25 +1,40 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
-use telegram_bot_rust::{AppState, Lang, effective_lang_from_parts};
+use telegram_bot_rust::lang::{default_lang_aliases, fallback_chain, parse_lang_aliases};
+use telegram_bot_rust::{
+    AppState, Config, Lang, LangPreferences, effective_lang, load_all_messages, negotiate_lang,
+    parse_lang, supported_lang,
+};
+
+fn lang(tag: &str) -> Lang {
+    parse_lang(tag).unwrap()
+}
+
+fn shipped_config() -> Config {
+    Config {
+        messages: load_all_messages("messages").into(),
+        ..Default::default()
+    }
+}
 
 #[test]
 fn detects_language_from_message_language_code() {
     let state = Arc::new(tokio::sync::RwLock::new(AppState {
         by_user: HashMap::new(),
         user_langs: HashMap::new(),
-        chat_langs: HashMap::new(),
         seen_welcome: HashMap::new(),
         user_start_attempts: HashMap::new(),
         user_miss_streaks: HashMap::new(),
+        ..Default::default()
     }));
 
     let rt = Runtime::new().unwrap();
-    let detected = rt.block_on(async {
-        effective_lang_from_parts(&state, Some("it"), Some(200), 100, Lang::En).await
-    });
-    assert_eq!(detected, Lang::It);
+    let config = shipped_config();
+    let detected =
+        rt.block_on(async { effective_lang(&state, &config, 100, Some(200), Some("it")).await });
+    assert_eq!(detected, lang("it"));
 }
 
 #[test]
@@ -27,16 +42,104 @@ fn detects_language_from_message_language_code_prefix() {
     let state = Arc::new(tokio::sync::RwLock::new(AppState {
         by_user: HashMap::new(),
         user_langs: HashMap::new(),
-        chat_langs: HashMap::new(),
         seen_welcome: HashMap::new(),
         user_start_attempts: HashMap::new(),
         user_miss_streaks: HashMap::new(),
+        ..Default::default()
     }));
 
     let rt = Runtime::new().unwrap();
-    // simulate a locale-style language_code like "en-US"; the code should accept the prefix
-    let detected = rt.block_on(async {
-        effective_lang_from_parts(&state, Some("en-US"), Some(300), 101, Lang::It).await
-    });
-    assert_eq!(detected, Lang::En);
+    // a locale-style language_code like "en-US" resolves to the `en` messages
+    // along its fallback chain
+    let config = Config {
+        lang: lang("it"),
+        ..shipped_config()
+    };
+    let detected =
+        rt.block_on(async { effective_lang(&state, &config, 101, Some(300), Some("en-US")).await });
+    assert_eq!(detected, Lang::en());
+    let messages = load_all_messages("messages");
+    assert_eq!(supported_lang(&messages, "en-US"), Some(Lang::en()));
+}
+
+#[test]
+fn fallback_chains_drop_subtags_from_the_end() {
+    let chain = |tag: &str| -> Vec<String> {
+        fallback_chain(&lang(tag))
+            .iter()
+            .map(|l| l.tag().to_string())
+            .collect()
+    };
+    assert_eq!(chain("zh-Hant-TW"), vec!["zh-Hant-TW", "zh-Hant", "zh"]);
+    assert_eq!(chain("pt-BR"), vec!["pt-BR", "pt"]);
+    assert_eq!(chain("en-US-x-twain"), vec!["en-US-x-twain", "en-US", "en"]);
+    assert_eq!(chain("it"), vec!["it"]);
+}
+
+#[test]
+fn negotiation_follows_priorities_chains_and_aliases() {
+    let available: HashSet<&str> = ["en", "it", "zh", "zh-Hant", "pt", "he"].into();
+    let aliases = default_lang_aliases();
+    let negotiate = |prefs: LangPreferences<'_>| {
+        negotiate_lang(prefs, &aliases, &Lang::en(), |t| available.contains(t))
+            .tag()
+            .to_string()
+    };
+    let code = |c: &'static str| LangPreferences {
+        language_code: Some(c),
+        ..Default::default()
+    };
+
+    assert_eq!(negotiate(code("zh-Hant-TW")), "zh-Hant");
+    assert_eq!(negotiate(code("zh-Hans-CN")), "zh");
+    assert_eq!(negotiate(code("pt-BR")), "pt");
+    assert_eq!(negotiate(code("de-AT")), "en");
+    // aliases: deprecated codes and regions that imply a script
+    assert_eq!(negotiate(code("iw")), "he");
+    assert_eq!(negotiate(code("zh-TW")), "zh-Hant");
+    // malformed and non-ASCII codes fall back instead of panicking
+    assert_eq!(negotiate(code("é")), "en");
+    assert_eq!(negotiate(code("日本語")), "en");
+    assert_eq!(negotiate(LangPreferences::default()), "en");
+
+    // user override > chat override > language_code
+    let (it, pt, fr) = (lang("it"), lang("pt-PT"), lang("fr"));
+    let all = LangPreferences {
+        user: Some(&it),
+        chat: Some(&pt),
+        language_code: Some("zh"),
+    };
+    assert_eq!(negotiate(all), "it");
+    assert_eq!(negotiate(LangPreferences { user: None, ..all }), "pt");
+    // an override without messages (e.g. removed by a reload) is skipped
+    assert_eq!(
+        negotiate(LangPreferences {
+            user: Some(&fr),
+            chat: None,
+            ..all
+        }),
+        "zh"
+    );
+}
+
+#[test]
+fn configured_aliases_are_parsed_and_validated() {
+    let aliases = parse_lang_aliases("pt-PT = pt_BR, gsw=de").unwrap();
+    assert_eq!(aliases["pt-PT"], lang("pt-BR"));
+    assert_eq!(aliases["gsw"], lang("de"));
+    assert!(parse_lang_aliases("").unwrap().is_empty());
+    assert!(parse_lang_aliases("pt-PT").is_err());
+    assert!(parse_lang_aliases("pt-PT=!!").is_err());
+
+    let available: HashSet<&str> = ["en", "de"].into();
+    let found = negotiate_lang(
+        LangPreferences {
+            language_code: Some("gsw-CH"),
+            ..Default::default()
+        },
+        &aliases,
+        &Lang::en(),
+        |t| available.contains(t),
+    );
+    assert_eq!(found, lang("de"));
 }
@@ -0,0 +1,66 @@
+use std::fs;
+use telegram_bot_rust::{Lang, load_message_catalog, parse_lang, supported_lang};
+
+fn tag(s: &str) -> Option<String> {
+    parse_lang(s).map(|l| l.tag().to_string())
+}
+
+#[test]
+fn bcp47_tags_are_validated_and_normalized() {
+    assert_eq!(tag("de"), Some("de".to_string()));
+    assert_eq!(tag("pt_br"), Some("pt-BR".to_string()));
+    assert_eq!(tag("ZH-hant-tw"), Some("zh-Hant-TW".to_string()));
+    assert_eq!(tag("es-419"), Some("es-419".to_string()));
+    assert_eq!(tag("sl-rozaj-biske"), Some("sl-rozaj-biske".to_string()));
+    assert_eq!(
+        tag("en-US-u-ca-gregory"),
+        Some("en-US-u-ca-gregory".to_string())
+    );
+    assert_eq!(tag("en-x-pirate"), Some("en-x-pirate".to_string()));
+    assert_eq!(parse_lang("de-AT").unwrap().primary(), "de");
+
+    for bad in [
+        "",
+        "e",
+        "languages",
+        "en-",
+        "en--US",
+        "en-US-u",
+        "1234",
+        "de.json",
+    ] {
+        assert_eq!(parse_lang(bad), None, "{}", bad);
+    }
+}
+
+#[test]
+fn dropping_a_file_adds_a_language() {
+    let dir = std::env::temp_dir().join(format!("languages_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("de.json"),
+        r#"{"language_name": "Deutsch", "pong": "pong!"}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("pt_br.json"), r#"{"language_name": "Português"}"#).unwrap();
+    fs::write(dir.join("not a language.json"), "{}").unwrap();
+
+    let catalog = load_message_catalog(dir.to_str().unwrap(), &Lang::en());
+    let mut tags: Vec<&String> = catalog.messages.keys().collect();
+    tags.sort_unstable();
+    assert_eq!(tags, vec!["de", "pt-BR"]);
+    assert_eq!(catalog.messages["de"].language_name, "Deutsch");
+    assert_eq!(catalog.messages["de"].tag, "de");
+
+    // `/lang de` and a Telegram language_code "de-CH" both select German
+    assert_eq!(supported_lang(&catalog.messages, "de"), parse_lang("de"));
+    assert_eq!(supported_lang(&catalog.messages, "de-CH"), parse_lang("de"));
+    assert_eq!(
+        supported_lang(&catalog.messages, "pt-br"),
+        parse_lang("pt-BR")
+    );
+    assert_eq!(supported_lang(&catalog.messages, "fr"), None);
+
+    let _ = fs::remove_dir_all(&dir);
+}
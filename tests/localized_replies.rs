@@ -0,0 +1,138 @@
+use serde_json::{Map, Value};
+use telegram_bot_rust::bans::{Ban, BanList, BanScope};
+use telegram_bot_rust::keyboards::language_keyboard;
+use telegram_bot_rust::roles::{Role, RoleGrants};
+use telegram_bot_rust::{
+    Messages, bans_listing, language_listing, load_all_messages, load_message_catalog,
+    merge_messages, parse_lang, roles_listing,
+};
+use teloxide::types::InlineKeyboardButtonKind;
+
+#[test]
+fn lang_listing_is_written_in_the_user_language() {
+    let catalog = load_message_catalog("messages", &parse_lang("en").unwrap());
+    let it = &catalog.messages["it"];
+    let (reply, entries) = language_listing(it, &catalog.messages);
+
+    let mut lines = reply.lines();
+    assert_eq!(lines.next(), Some("Lingua corrente: Italiano (it)"));
+    let available = lines.next().unwrap();
+    assert!(
+        available.starts_with("Lingue disponibili: "),
+        "{}",
+        available
+    );
+    assert!(available.contains("English (en)"), "{}", available);
+    assert!(!reply.contains("Available languages"));
+
+    // one picker entry per loaded language, sorted by tag
+    let tags: Vec<&str> = entries.iter().map(|(t, _)| t.as_str()).collect();
+    let mut sorted = tags.clone();
+    sorted.sort_unstable();
+    assert_eq!(tags, sorted);
+    assert_eq!(tags.len(), catalog.messages.len());
+}
+
+#[test]
+fn lang_listing_follows_the_catalog_templates() {
+    let catalog = load_message_catalog("messages", &parse_lang("en").unwrap());
+    let mut ru = catalog.messages["ru"].clone();
+    ru.language_entry = "{tag}: {name}".to_string();
+    ru.current_language = "{language} ← {label}".to_string();
+    let (reply, entries) = language_listing(&ru, &catalog.messages);
+
+    assert!(
+        reply.starts_with("ru: русский ← Текущий язык:"),
+        "{}",
+        reply
+    );
+    assert!(reply.contains("Доступные языки: "), "{}", reply);
+    assert!(reply.contains("it: Italiano"), "{}", reply);
+
+    // the picker buttons carry the same localized labels
+    let markup = language_keyboard(&entries);
+    let labels: Vec<&str> = markup
+        .inline_keyboard
+        .iter()
+        .flatten()
+        .filter(|b| matches!(b.kind, InlineKeyboardButtonKind::CallbackData(_)))
+        .map(|b| b.text.as_str())
+        .collect();
+    assert!(labels.contains(&"it: Italiano"), "{:?}", labels);
+}
+
+/// The Italian catalog with every line of every template tagged with its
+/// key, so a reply line without a tag was not written by the catalog
+fn tagged_italian() -> Messages {
+    let Value::Object(entries) =
+        serde_json::to_value(&load_all_messages("messages")["it"]).unwrap()
+    else {
+        unreachable!("Messages serializes to an object");
+    };
+    let tagged: Map<String, Value> = entries
+        .into_iter()
+        .map(|(key, value)| {
+            let text = value.as_str().unwrap_or_default();
+            let marker = format!("⟦{}⟧", key);
+            let tagged = format!("{}{}", marker, text.replace('\n', &format!("\n{}", marker)));
+            (key, Value::String(tagged))
+        })
+        .collect();
+    let mut messages = merge_messages(&[&tagged]);
+    messages.tag = "it".to_string();
+    messages
+}
+
+/// Every line of `reply` was written by one of `keys`
+fn assert_from_catalog(reply: &str, keys: &[&str]) {
+    for line in reply.lines() {
+        assert!(
+            keys.iter().any(|k| line.contains(&format!("⟦{}⟧", k))),
+            "line not from {:?}: {:?}",
+            keys,
+            line
+        );
+    }
+    assert!(!reply.is_empty());
+}
+
+#[test]
+fn admin_replies_come_from_the_catalog() {
+    let it = tagged_italian();
+    let all = load_all_messages("messages");
+
+    let (reply, entries) = language_listing(&it, &all);
+    assert_from_catalog(&reply, &["current_language", "available_languages"]);
+    for (_, label) in &entries {
+        assert!(label.contains("⟦language_entry⟧"), "{}", label);
+    }
+
+    let mut grants = RoleGrants::default();
+    grants.grant(-100, 8, Role::Moderator);
+    for (owner, role) in [(Some(1), Some(Role::Moderator)), (None, None)] {
+        let reply = roles_listing(&it, owner, &grants, -100, role);
+        assert_from_catalog(&reply, &["roles_list"]);
+    }
+    // the missing owner and admins are the catalog's "none", not English
+    let reply = roles_listing(&it, None, &grants, -100, None);
+    assert!(reply.contains("⟦roles_none⟧"), "{}", reply);
+    assert!(reply.contains("⟦role_player⟧"), "{}", reply);
+
+    let mut bans = BanList::default();
+    let scope = BanScope::Chat(-100);
+    assert_from_catalog(&bans_listing(&it, &bans, scope, 0), &["bans_none"]);
+    let ban = Ban {
+        by: 1,
+        at: 0,
+        until: None,
+        reason: Some("spam".to_string()),
+    };
+    bans.ban(scope, 7, ban.clone());
+    bans.ban(BanScope::Global, 8, ban);
+    for scope in [scope, BanScope::Global] {
+        let reply = bans_listing(&it, &bans, scope, 0);
+        assert_from_catalog(&reply, &["bans_list", "bans_entry"]);
+    }
+
+    assert_from_catalog(&it.not_authorized, &["not_authorized"]);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::temp_messages_dir;
+use std::collections::HashMap;
+use std::fs;
+use telegram_bot_rust::{
+    Lang, default_messages, load_message_catalog, lookup_lang, message_file_tags,
+    missing_message_keys, parse_lang,
+};
+
+#[test]
+fn missing_keys_fall_back_one_by_one() {
+    let dir = temp_messages_dir("chain");
+    // the default language lacks `pong`, Russian lacks `pong` and `too_low`
+    fs::write(
+        dir.join("it.json"),
+        r#"{"too_low": "Troppo basso. Tentativi rimasti: {attempts}", "language_name": "Italiano"}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("ru.json"),
+        r#"{"too_high": "Слишком много. Осталось попыток: {attempts}", "language_name": "Русский"}"#,
+    )
+    .unwrap();
+
+    let catalog = load_message_catalog(dir.to_str().unwrap(), &parse_lang("it").unwrap());
+    let ru = &catalog.messages["ru"];
+    // own key is kept, the rest of the language is not replaced
+    assert_eq!(ru.language_name, "Русский");
+    assert!(ru.too_high.starts_with("Слишком"));
+    // missing in ru -> taken from the default language (it)
+    assert!(ru.too_low.starts_with("Troppo basso"));
+    // missing in both -> English built-in
+    assert_eq!(ru.pong, default_messages().pong);
+
+    let ru_missing = &catalog.missing_keys["ru"];
+    assert!(ru_missing.contains(&"too_low".to_string()));
+    assert!(ru_missing.contains(&"pong".to_string()));
+    assert!(!ru_missing.contains(&"too_high".to_string()));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn malformed_file_falls_back_entirely_and_is_reported() {
+    let dir = temp_messages_dir("malformed");
+    fs::write(dir.join("zh.json"), "{ not json").unwrap();
+    let catalog = load_message_catalog(dir.to_str().unwrap(), &Lang::en());
+    assert_eq!(catalog.messages["zh"].too_low, default_messages().too_low);
+    assert_eq!(
+        catalog.missing_keys["zh"],
+        missing_message_keys(&serde_json::Map::new())
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn shipped_translations_are_complete() {
+    let catalog = load_message_catalog("messages", &Lang::en());
+    assert!(
+        catalog.missing_keys.is_empty(),
+        "translations with missing keys: {:?}",
+        catalog.missing_keys
+    );
+}
+
+#[test]
+fn regional_default_lang_resolves_to_its_file_before_loading() {
+    let dir = temp_messages_dir("regional");
+    fs::write(
+        dir.join("it.json"),
+        r#"{"too_low": "Troppo basso. Tentativi rimasti: {attempts}"}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("ru.json"), r#"{"language_name": "Русский"}"#).unwrap();
+
+    // DEFAULT_LANG=it-IT has no file of its own: it resolves to it.json
+    let dir_str = dir.to_str().unwrap();
+    let tags = message_file_tags(dir_str);
+    let resolved = lookup_lang(&parse_lang("it-IT").unwrap(), &HashMap::new(), |t| {
+        tags.contains(t)
+    })
+    .unwrap();
+    assert_eq!(resolved.tag(), "it");
+
+    // so missing keys fall back to Italian, as on /reload_messages
+    let catalog = load_message_catalog(dir_str, &resolved);
+    assert!(catalog.messages["ru"].too_low.starts_with("Troppo basso"));
+    let _ = fs::remove_dir_all(&dir);
+}
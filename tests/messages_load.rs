@@ -9,12 +9,12 @@ fn messages_dir_loads_all_known_languages() {
     let mut expected = Vec::new();
     if let Ok(entries) = fs::read_dir(dir) {
         for e in entries.flatten() {
-            if let Some(fname) = e.file_name().to_str() {
-                if fname.to_lowercase().ends_with(".json") {
-                    let stem = fname.trim_end_matches(".json");
-                    if let Some(_lang) = parse_lang(stem) {
-                        expected.push(stem.to_string());
-                    }
+            if let Some(fname) = e.file_name().to_str()
+                && fname.to_lowercase().ends_with(".json")
+            {
+                let stem = fname.trim_end_matches(".json");
+                if let Some(_lang) = parse_lang(stem) {
+                    expected.push(stem.to_string());
                 }
             }
         }
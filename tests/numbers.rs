@@ -0,0 +1,87 @@
+use telegram_bot_rust::load_all_messages;
+use telegram_bot_rust::numbers::{format_number, parse_guess, parse_localized};
+
+#[test]
+fn digits_of_every_script_are_accepted() {
+    assert_eq!(parse_guess("42", "en"), Some(42));
+    assert_eq!(parse_guess(" 42. ", "en"), Some(42));
+    assert_eq!(parse_guess("42!", "it"), Some(42));
+    assert_eq!(parse_guess("-7", "en"), Some(-7));
+    assert_eq!(parse_guess("٤٢", "ar"), Some(42));
+    assert_eq!(parse_guess("۴۲", "en"), Some(42));
+    assert_eq!(parse_guess("４２", "zh"), Some(42));
+    assert_eq!(parse_guess("４２。", "zh"), Some(42));
+    assert_eq!(parse_guess("4 2", "en"), None);
+    assert_eq!(parse_guess("42a", "en"), None);
+    assert_eq!(parse_guess("99999999999", "en"), None);
+    assert_eq!(parse_guess("", "en"), None);
+    assert_eq!(parse_guess(".", "en"), None);
+}
+
+#[test]
+fn chinese_numerals_are_accepted() {
+    assert_eq!(parse_guess("四十二", "zh"), Some(42));
+    assert_eq!(parse_guess("四二", "zh"), Some(42));
+    assert_eq!(parse_guess("十二", "zh"), Some(12));
+    assert_eq!(parse_guess("十", "zh"), Some(10));
+    assert_eq!(parse_guess("一百", "zh"), Some(100));
+    assert_eq!(parse_guess("一百零五", "zh"), Some(105));
+    assert_eq!(parse_guess("两千", "zh-Hant"), Some(2000));
+    assert_eq!(parse_guess("三万五千", "zh"), Some(35000));
+    assert_eq!(parse_guess("亿亿亿亿亿", "zh"), None);
+}
+
+#[test]
+fn number_words_follow_the_user_language() {
+    assert_eq!(parse_guess("forty-two", "en"), Some(42));
+    assert_eq!(parse_guess("Forty two", "en-GB"), Some(42));
+    assert_eq!(parse_guess("quarantadue", "it"), Some(42));
+    assert_eq!(parse_guess("ventotto", "it"), Some(28));
+    assert_eq!(parse_guess("ventitré", "it"), Some(23));
+    assert_eq!(parse_guess("ventitre", "it"), Some(23));
+    assert_eq!(parse_guess("сорок два", "ru"), Some(42));
+    assert_eq!(parse_guess("Сто", "ru"), Some(100));
+    assert_eq!(parse_guess("اثنان وأربعون", "ar"), Some(42));
+    assert_eq!(parse_guess("اثنين و اربعين", "ar"), Some(42));
+    assert_eq!(parse_guess("ثلاثة عشر", "ar"), Some(13));
+    // words of another language are not guesses
+    assert_eq!(parse_guess("quarantadue", "en"), None);
+    assert_eq!(parse_guess("hello", "en"), None);
+}
+
+#[test]
+fn numbers_are_formatted_per_language() {
+    assert_eq!(format_number(42, "en"), "42");
+    assert_eq!(format_number(1234567, "en"), "1,234,567");
+    assert_eq!(format_number(1234, "it"), "1.234");
+    assert_eq!(format_number(-1234, "ru"), "-1\u{202F}234");
+    assert_eq!(format_number(42, "ar"), "٤٢");
+    assert_eq!(format_number(1234, "ar-EG"), "١٬٢٣٤");
+    assert_eq!(format_number(42, "zh"), "42");
+    assert_eq!(format_number("n/a", "ar"), "n/a");
+    for (n, tag) in [(1234567, "ar"), (-1234, "ru"), (987654, "it"), (5, "en")] {
+        assert_eq!(parse_localized(&format_number(n, tag)), Some(n));
+    }
+}
+
+#[test]
+fn localized_numbers_pick_plural_forms() {
+    let all = load_all_messages("messages");
+    let ar = &all["ar"];
+    let two = ar.format(&ar.too_low, &[("attempts", &ar.number(2))]);
+    assert!(two.contains("محاولتان"), "{:?}", two);
+    let five = ar.format(&ar.too_low, &[("attempts", &ar.number(5))]);
+    assert!(five.contains("٥"), "{:?}", five);
+    assert!(!five.contains('5'));
+
+    let ru = &all["ru"];
+    let started = ru.format(
+        &ru.game_started,
+        &[
+            ("min", &ru.number(1)),
+            ("max", &ru.number(1000)),
+            ("attempts", &ru.number(3)),
+        ],
+    );
+    assert!(started.contains("1\u{202F}000"), "{:?}", started);
+}
@@ -0,0 +1,88 @@
+use telegram_bot_rust::load_all_messages;
+use telegram_bot_rust::plural::{PluralCategory, format_message, plural_category};
+
+#[test]
+fn cldr_categories_for_supported_languages() {
+    assert_eq!(plural_category("en", 1), PluralCategory::One);
+    assert_eq!(plural_category("it", 0), PluralCategory::Other);
+    assert_eq!(plural_category("zh", 1), PluralCategory::Other);
+
+    let ru: Vec<PluralCategory> = [1, 2, 5, 11, 12, 21, 22, 25, 111]
+        .iter()
+        .map(|n| plural_category("ru", *n))
+        .collect();
+    use PluralCategory::*;
+    assert_eq!(ru, vec![One, Few, Many, Many, Many, One, Few, Many, Many]);
+
+    let ar: Vec<PluralCategory> = [0, 1, 2, 3, 10, 11, 99, 100, 103]
+        .iter()
+        .map(|n| plural_category("ar", *n))
+        .collect();
+    assert_eq!(ar, vec![Zero, One, Two, Few, Few, Many, Many, Other, Few]);
+}
+
+#[test]
+fn plural_and_select_blocks_are_resolved() {
+    let t = "{n, plural, =0 {none} one {# item} other {# items}} in {where}";
+    assert_eq!(
+        format_message(t, "en", &[("n", "0"), ("where", "box")]),
+        "none in box"
+    );
+    assert_eq!(
+        format_message(t, "en", &[("n", "1"), ("where", "box")]),
+        "1 item in box"
+    );
+    assert_eq!(
+        format_message(t, "en", &[("n", "7"), ("where", "box")]),
+        "7 items in box"
+    );
+
+    let s = "{kind, select, win {won} other {lost}}";
+    assert_eq!(format_message(s, "en", &[("kind", "win")]), "won");
+    assert_eq!(format_message(s, "en", &[("kind", "draw")]), "lost");
+
+    // malformed blocks and unknown keys are left untouched
+    let broken = "{n, plural, one {x} other}";
+    assert_eq!(format_message(broken, "en", &[("n", "1")]), broken);
+    assert_eq!(
+        format_message("{x, plural, one {a}}", "en", &[]),
+        "{x, plural, one {a}}"
+    );
+}
+
+#[test]
+fn translations_pick_the_right_form() {
+    let map = load_all_messages("messages");
+    let ru = &map["ru"];
+    let too_low = |n: &str| ru.format(&ru.too_low, &[("attempts", n)]);
+    assert!(too_low("1").ends_with("Осталась 1 попытка"));
+    assert!(too_low("3").ends_with("Осталось 3 попытки"));
+    assert!(too_low("5").ends_with("Осталось 5 попыток"));
+
+    let ar = &map["ar"];
+    assert!(
+        ar.format(&ar.too_high, &[("attempts", "2")])
+            .ends_with("تتبقى محاولتان")
+    );
+
+    let en = &map["en"];
+    let started = en.format(
+        &en.game_started,
+        &[("min", "1"), ("max", "100"), ("attempts", "1")],
+    );
+    assert!(started.ends_with("You have 1 attempt."), "{}", started);
+
+    // no raw plural syntax survives in any language
+    for (tag, m) in map.iter() {
+        let text = m.format(
+            &m.success_correct,
+            &[("next_attempts", "4"), ("number_attempts", "2")],
+        );
+        assert!(
+            !text.contains('{') && !text.contains('#'),
+            "{}: {}",
+            tag,
+            text
+        );
+    }
+}
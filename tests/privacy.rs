@@ -0,0 +1,134 @@
+use telegram_bot_rust::bans::{Ban, BanList, BanScope};
+use telegram_bot_rust::privacy::{erase_user_data, export_user_data};
+use telegram_bot_rust::rating::UserStats;
+use telegram_bot_rust::roles::Role;
+use telegram_bot_rust::seasons::{SeasonPeriod, SeasonState};
+use telegram_bot_rust::{AppState, GameState, is_command, parse_lang};
+
+fn state_with_two_users() -> AppState {
+    let mut state = AppState::default();
+    // user 7 plays in chats 1 and 2, user 8 only in chat 1
+    for (chat, user) in [(1i64, 7u64), (2, 7), (1, 8)] {
+        let composite = format!("{}:{}", chat, user);
+        state.seen_welcome.insert(composite.clone(), 100);
+        state.user_start_attempts.insert(composite.clone(), 9);
+        state.user_miss_streaks.insert(composite.clone(), 1);
+        state.user_win_streaks.insert(composite.clone(), 1);
+        state
+            .user_stats
+            .insert(composite.clone(), UserStats::default());
+    }
+    state.by_user.insert(
+        (1, 7),
+        GameState {
+            target: 42,
+            attempts_left: 4,
+            start_attempts: 9,
+            ..Default::default()
+        },
+    );
+    state.user_langs.insert((2, 7), parse_lang("it").unwrap());
+    state.season = SeasonState::new(SeasonPeriod::Monthly, 0);
+    state.season.record_win(1, 7, "Anna", 5);
+    state.season.record_win(1, 8, "Luca", 3);
+    let record = state.season.close(SeasonPeriod::Monthly, 1);
+    state.season_archive.push(record);
+    state.season.record_win(2, 7, "Anna", 2);
+    // a user id that is a suffix of 7's key must not match
+    state.user_start_attempts.insert("1:77".to_string(), 5);
+    state.keypad_input.insert((1, 7), "12".to_string());
+    state.pending_resets.insert(7, 100);
+    state.pending_erasures.insert(7, (7, 100));
+    // user 8 is about to erase 7's data as an admin
+    state.pending_erasures.insert(8, (7, 100));
+    state.role_grants.grant(0, 7, Role::GlobalAdmin);
+    state.role_grants.grant(1, 7, Role::Moderator);
+    state.role_grants.grant(1, 8, Role::Moderator);
+    state
+}
+
+#[test]
+fn export_contains_every_map_for_the_user_only() {
+    let state = state_with_two_users();
+    let export = export_user_data(&state, &BanList::default(), 7);
+
+    assert_eq!(export["user_id"], 7);
+    assert_eq!(export["user_start_attempts"]["1"], 9);
+    assert_eq!(export["user_start_attempts"]["2"], 9);
+    assert_eq!(export["user_start_attempts"].as_object().unwrap().len(), 2);
+    assert_eq!(export["seen_welcome"].as_object().unwrap().len(), 2);
+    assert_eq!(export["user_win_streaks"]["1"], 1);
+    assert_eq!(export["language_preferences"]["2"], "it");
+    assert_eq!(export["season_scores"]["2"]["points"], 2);
+    assert_eq!(export["season_champion_of"][0]["season"], 1);
+    assert_eq!(export["active_games"]["1"]["attempts_left"], 4);
+    // never leak the number to guess
+    assert!(!export.to_string().contains("42"));
+}
+
+#[test]
+fn erase_removes_the_user_everywhere_and_keeps_others() {
+    let mut state = state_with_two_users();
+    let removed = erase_user_data(&mut state, 7);
+    // 2 chats x 5 maps, 1 game, 1 language, 1 season score, 1 champion,
+    // 1 keypad input, 2 pending confirmations, 2 roles
+    assert_eq!(removed, 19);
+
+    let export = export_user_data(&state, &BanList::default(), 7);
+    assert_eq!(export["user_start_attempts"].as_object().unwrap().len(), 0);
+    assert!(export["season_champion_of"].as_array().unwrap().is_empty());
+    assert!(state.by_user.is_empty());
+    assert!(state.user_langs.is_empty());
+    assert!(state.keypad_input.is_empty());
+    assert!(state.pending_resets.is_empty());
+    assert_eq!(export["roles"]["global_admin"], false);
+    assert!(
+        export["roles"]["moderator_of"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+    // the admin's pending erasure is the admin's, not the user's
+    assert_eq!(state.pending_erasures.get(&8), Some(&(7, 100)));
+    assert!(!state.pending_erasures.contains_key(&7));
+    assert_eq!(state.role_grants.moderated_chats(8), vec![1]);
+
+    assert!(state.user_start_attempts.contains_key("1:8"));
+    assert!(state.user_start_attempts.contains_key("1:77"));
+    assert!(state.user_stats.contains_key("1:8"));
+    assert_eq!(erase_user_data(&mut state, 7), 0);
+}
+
+#[test]
+fn export_lists_the_bans_of_the_user_and_erasure_keeps_them() {
+    let mut state = state_with_two_users();
+    let mut bans = BanList::default();
+    let ban = |reason: &str| Ban {
+        by: 99,
+        at: 100,
+        until: Some(200),
+        reason: Some(reason.to_string()),
+    };
+    bans.ban(BanScope::Chat(1), 7, ban("spam"));
+    bans.ban(BanScope::Chat(1), 8, ban("flood"));
+    bans.ban(BanScope::Global, 8, ban("abuse"));
+
+    let export = export_user_data(&state, &bans, 7);
+    assert_eq!(export["bans"]["chats"]["1"]["reason"], "spam");
+    assert_eq!(export["bans"]["chats"]["1"]["until"], 200);
+    assert!(export["bans"]["global"].is_null());
+    // neither other users' bans nor who issued them
+    assert!(!export.to_string().contains("flood"));
+    assert!(!export.to_string().contains("99"));
+
+    erase_user_data(&mut state, 7);
+    let export = export_user_data(&state, &bans, 7);
+    assert_eq!(export["bans"]["chats"]["1"]["reason"], "spam");
+}
+
+#[test]
+fn only_the_exact_command_starts_an_erasure() {
+    assert!(is_command("/cancella_dati conferma", "/cancella_dati"));
+    assert!(is_command("/cancella_dati@guess_bot 42", "/cancella_dati"));
+    assert!(!is_command("/cancella_dati_vecchi", "/cancella_dati"));
+}
@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+use telegram_bot_rust::ratelimit::{Limited, RateLimit, RateLimiter, RateLimits, Verdict};
+
+fn limiter(user: Option<RateLimit>, chat: Option<RateLimit>) -> RateLimiter {
+    RateLimiter::new(RateLimits {
+        user,
+        chat,
+        notice_window: Duration::from_secs(60),
+    })
+}
+
+fn allowed(v: Verdict) -> bool {
+    v == Verdict::Allow
+}
+
+#[test]
+fn a_burst_is_allowed_then_refilled_over_time() {
+    // 3 at once, then one every 2 seconds
+    let mut rl = limiter(RateLimit::new(3, 30), None);
+    let t0 = Instant::now();
+    for _ in 0..3 {
+        assert!(allowed(rl.check(-100, Some(7), t0)));
+    }
+    assert_eq!(
+        rl.check(-100, Some(7), t0),
+        Verdict::Drop {
+            limited: Limited::User,
+            notify: true
+        }
+    );
+    assert!(!allowed(rl.check(
+        -100,
+        Some(7),
+        t0 + Duration::from_secs(1)
+    )));
+    assert!(allowed(rl.check(
+        -100,
+        Some(7),
+        t0 + Duration::from_secs(3)
+    )));
+    // the bucket never holds more than the burst
+    let later = t0 + Duration::from_secs(3600);
+    for _ in 0..3 {
+        assert!(allowed(rl.check(-100, Some(7), later)));
+    }
+    assert!(!allowed(rl.check(-100, Some(7), later)));
+}
+
+#[test]
+fn users_are_limited_across_chats_but_independently_of_each_other() {
+    let mut rl = limiter(RateLimit::new(2, 1), None);
+    let t0 = Instant::now();
+    assert!(allowed(rl.check(-100, Some(7), t0)));
+    assert!(allowed(rl.check(-200, Some(7), t0)));
+    assert!(!allowed(rl.check(-300, Some(7), t0)));
+    assert!(allowed(rl.check(-100, Some(8), t0)));
+}
+
+#[test]
+fn the_chat_bucket_is_shared_by_its_members() {
+    let mut rl = limiter(RateLimit::new(5, 60), RateLimit::new(3, 60));
+    let t0 = Instant::now();
+    for user in 1..=3 {
+        assert!(allowed(rl.check(-100, Some(user), t0)));
+    }
+    assert_eq!(
+        rl.check(-100, Some(4), t0),
+        Verdict::Drop {
+            limited: Limited::Chat,
+            notify: true
+        }
+    );
+    // one notice per chat, whoever is writing
+    assert_eq!(
+        rl.check(-100, Some(5), t0),
+        Verdict::Drop {
+            limited: Limited::Chat,
+            notify: false
+        }
+    );
+    // other chats are not affected
+    assert!(allowed(rl.check(-200, Some(4), t0)));
+}
+
+#[test]
+fn a_dropped_message_takes_no_token() {
+    let mut rl = limiter(RateLimit::new(1, 1), RateLimit::new(2, 1));
+    let t0 = Instant::now();
+    assert!(allowed(rl.check(-100, Some(7), t0)));
+    // user 7 is over its limit: the chat bucket keeps its last token
+    for _ in 0..10 {
+        assert!(!allowed(rl.check(-100, Some(7), t0)));
+    }
+    assert!(allowed(rl.check(-100, Some(8), t0)));
+}
+
+#[test]
+fn the_notice_is_sent_once_per_window() {
+    let mut rl = limiter(RateLimit::new(1, 1), None);
+    let t0 = Instant::now();
+    assert!(allowed(rl.check(-100, Some(7), t0)));
+    let notify = |v: Verdict| matches!(v, Verdict::Drop { notify: true, .. });
+    assert!(notify(rl.check(-100, Some(7), t0)));
+    assert!(!notify(rl.check(
+        -100,
+        Some(7),
+        t0 + Duration::from_secs(30)
+    )));
+    // a new window starts 60 seconds after the last notice (the refilled
+    // token goes first)
+    let later = t0 + Duration::from_millis(60_500);
+    assert!(allowed(rl.check(-100, Some(7), later)));
+    assert!(notify(rl.check(-100, Some(7), later)));
+}
+
+#[test]
+fn disabled_limits_allow_everything() {
+    assert_eq!(RateLimit::new(0, 30), None);
+    assert_eq!(RateLimit::new(5, 0), None);
+    let mut rl = limiter(None, None);
+    let t0 = Instant::now();
+    for _ in 0..1000 {
+        assert!(allowed(rl.check(-100, Some(7), t0)));
+    }
+    // anonymous senders only count against the chat
+    assert!(allowed(rl.check(-100, None, t0)));
+    assert_eq!(rl.tracked(), (0, 0));
+}
+
+#[test]
+fn idle_buckets_are_forgotten() {
+    let mut rl = limiter(RateLimit::new(2, 60), RateLimit::new(2, 60));
+    let t0 = Instant::now();
+    rl.check(-100, Some(7), t0);
+    rl.check(-200, Some(8), t0);
+    assert_eq!(rl.tracked(), (2, 2));
+    // after the prune interval the buckets are full again and dropped
+    rl.check(-300, Some(9), t0 + Duration::from_secs(11 * 60));
+    assert_eq!(rl.tracked(), (1, 1));
+}
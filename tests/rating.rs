@@ -0,0 +1,98 @@
+use telegram_bot_rust::GameState;
+use telegram_bot_rust::rating::{Guess, UserStats, bisection_guesses, guess_quality, rate_game};
+
+#[test]
+fn bisection_is_rated_fully_efficient() {
+    let mut game = GameState {
+        target: 70,
+        attempts_left: 10,
+        start_attempts: 10,
+        low: 1,
+        high: 100,
+        ..Default::default()
+    };
+    // lower-midpoint bisection: 50, 75, 62, 68, 71, 69, 70
+    for g in [50, 75, 62, 68, 71, 69, 70] {
+        game.record_guess(g);
+    }
+    assert_eq!((game.low, game.high), (70, 70));
+    let rating = game.rating();
+    assert_eq!(rating.guesses, 7);
+    assert_eq!(rating.wasted, 0);
+    assert_eq!(rating.efficiency, 100);
+    assert_eq!(rating.optimal_guesses, bisection_guesses(1, 100, 70));
+    assert_eq!(rating.optimal_guesses, 7);
+}
+
+#[test]
+fn guesses_outside_the_known_interval_are_wasted() {
+    let mut game = GameState {
+        target: 30,
+        attempts_left: 5,
+        start_attempts: 5,
+        low: 1,
+        high: 100,
+        ..Default::default()
+    };
+    game.record_guess(50); // too high -> [1, 49]
+    game.record_guess(60); // already ruled out
+    game.record_guess(25); // too low -> [26, 49]
+    game.record_guess(10); // already ruled out
+    let rating = game.rating();
+    assert_eq!(rating.wasted, 2);
+    assert!(game.guesses[1].is_wasted());
+    assert!(!game.guesses[2].is_wasted());
+    // two perfect bisection guesses and two wasted ones
+    assert_eq!(rating.efficiency, 50);
+}
+
+#[test]
+fn edge_guesses_score_lower_than_midpoint_guesses() {
+    let mid = Guess {
+        value: 50,
+        low: 1,
+        high: 100,
+    };
+    let edge = Guess {
+        value: 1,
+        low: 1,
+        high: 100,
+    };
+    assert_eq!(guess_quality(&mid), 1.0);
+    assert!(guess_quality(&edge) < 0.1);
+    // a single remaining candidate is always a perfect guess
+    let last = Guess {
+        value: 7,
+        low: 7,
+        high: 7,
+    };
+    assert_eq!(guess_quality(&last), 1.0);
+    assert_eq!(rate_game(&[], 5).efficiency, 0);
+}
+
+#[test]
+fn stats_accumulate_ratings() {
+    let mut stats = UserStats::default();
+    let win = rate_game(
+        &[Guess {
+            value: 50,
+            low: 1,
+            high: 100,
+        }],
+        50,
+    );
+    let loss = rate_game(
+        &[Guess {
+            value: 1,
+            low: 1,
+            high: 100,
+        }],
+        50,
+    );
+    stats.record(true, &win);
+    stats.record(false, &loss);
+    assert_eq!((stats.games, stats.wins, stats.losses), (2, 1, 1));
+    assert_eq!(stats.guesses, 2);
+    assert_eq!(loss.efficiency, 2);
+    assert_eq!(stats.average_efficiency(), 51);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::temp_messages_dir;
+use std::collections::HashMap;
+use std::fs;
+use telegram_bot_rust::catalog::{MessageStore, reload_messages};
+use telegram_bot_rust::{Lang, load_all_messages, parse_lang};
+
+fn store_with_shipped_messages() -> MessageStore {
+    MessageStore::new(load_all_messages("messages"))
+}
+
+#[test]
+fn valid_files_are_swapped_in() {
+    let dir = temp_messages_dir("valid");
+    fs::write(
+        dir.join("it.json"),
+        r#"{"pong": "pong corretto", "too_low": "Troppo basso: {attempts}"}"#,
+    )
+    .unwrap();
+    let store = store_with_shipped_messages();
+    let before = store.snapshot();
+
+    let outcome = reload_messages(&store, dir.to_str().unwrap(), &parse_lang("it").unwrap())
+        .expect("valid catalog");
+    // English is always available, from the built-ins if need be
+    assert_eq!(outcome.languages, vec!["en".to_string(), "it".to_string()]);
+    assert!(outcome.missing_keys["it"].contains(&"too_high".to_string()));
+    assert_eq!(store.get("it").unwrap().pong, "pong corretto");
+    assert!(store.get("ru").is_none());
+    // snapshots taken before the reload are not affected
+    assert_eq!(before["it"].pong, load_all_messages("messages")["it"].pong);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn malformed_file_keeps_the_old_catalog() {
+    let dir = temp_messages_dir("malformed");
+    fs::write(dir.join("en.json"), r#"{"pong": "pong"}"#).unwrap();
+    fs::write(dir.join("it.json"), r#"{"pong": "pong", }"#).unwrap();
+    let store = store_with_shipped_messages();
+
+    let problems = reload_messages(&store, dir.to_str().unwrap(), &Lang::en()).unwrap_err();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("it.json"), "{:?}", problems);
+    assert_eq!(
+        store.get("it").unwrap().too_low,
+        load_all_messages("messages")["it"].too_low
+    );
+    assert!(store.get("ru").is_some());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn placeholder_problems_keep_the_old_catalog() {
+    let dir = temp_messages_dir("placeholders");
+    fs::write(
+        dir.join("it.json"),
+        r#"{"too_low": "Troppo basso: {tentativi}"}"#,
+    )
+    .unwrap();
+    let store = store_with_shipped_messages();
+
+    let problems = reload_messages(&store, dir.to_str().unwrap(), &Lang::en()).unwrap_err();
+    assert!(problems.iter().any(|p| p.contains("it.too_low")));
+    assert!(store.get("ru").is_some());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_default_language_or_empty_dir_is_rejected() {
+    let dir = temp_messages_dir("default");
+    fs::write(dir.join("it.json"), r#"{"pong": "pong"}"#).unwrap();
+    let store = MessageStore::from(HashMap::new());
+
+    let problems =
+        reload_messages(&store, dir.to_str().unwrap(), &parse_lang("ru").unwrap()).unwrap_err();
+    assert!(problems[0].contains("ru.json"), "{:?}", problems);
+
+    let empty = temp_messages_dir("empty");
+    let problems = reload_messages(&store, empty.to_str().unwrap(), &Lang::en()).unwrap_err();
+    assert!(
+        problems[0].starts_with("no message files"),
+        "{:?}",
+        problems
+    );
+    assert!(store.snapshot().is_empty());
+
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_dir_all(&empty);
+}
@@ -0,0 +1,77 @@
+use telegram_bot_rust::rating::UserStats;
+use telegram_bot_rust::resets::{ResetScope, reset_progress};
+use telegram_bot_rust::{AppState, is_command};
+
+fn state_with_progress() -> AppState {
+    let mut state = AppState::default();
+    // user 7 plays in chats 1 and 2, users 8 and 77 only in chat 1
+    for (chat, user) in [(1i64, 7u64), (2, 7), (1, 8), (1, 77)] {
+        let composite = format!("{}:{}", chat, user);
+        state.user_start_attempts.insert(composite.clone(), 8);
+        state.user_win_streaks.insert(composite.clone(), 2);
+        state
+            .user_stats
+            .insert(composite.clone(), UserStats::default());
+    }
+    // only a miss streak left for user 8 in chat 2
+    state.user_miss_streaks.insert("2:8".to_string(), 1);
+    state
+}
+
+#[test]
+fn user_scope_resets_one_user_in_one_chat() {
+    let mut state = state_with_progress();
+    let count = reset_progress(
+        &mut state,
+        ResetScope::User {
+            chat_id: 1,
+            user_id: 7,
+        },
+    );
+    assert_eq!(count, 1);
+    assert!(!state.user_start_attempts.contains_key("1:7"));
+    assert!(!state.user_win_streaks.contains_key("1:7"));
+    // same user in another chat and users with a similar id are kept
+    assert_eq!(state.user_start_attempts["2:7"], 8);
+    assert_eq!(state.user_start_attempts["1:77"], 8);
+    // statistics are not part of the progression
+    assert!(state.user_stats.contains_key("1:7"));
+
+    // nothing to reset is not an error
+    let again = reset_progress(
+        &mut state,
+        ResetScope::User {
+            chat_id: 1,
+            user_id: 7,
+        },
+    );
+    assert_eq!(again, 0);
+}
+
+#[test]
+fn chat_scope_resets_every_user_of_the_chat() {
+    let mut state = state_with_progress();
+    assert_eq!(reset_progress(&mut state, ResetScope::Chat(1)), 3);
+    assert_eq!(state.user_start_attempts.len(), 1);
+    assert!(state.user_start_attempts.contains_key("2:7"));
+    assert!(state.user_miss_streaks.contains_key("2:8"));
+    // keys present in only one of the maps count once
+    assert_eq!(reset_progress(&mut state, ResetScope::Chat(2)), 2);
+}
+
+#[test]
+fn all_scope_resets_everything_but_statistics() {
+    let mut state = state_with_progress();
+    assert_eq!(reset_progress(&mut state, ResetScope::All), 5);
+    assert!(state.user_start_attempts.is_empty());
+    assert!(state.user_miss_streaks.is_empty());
+    assert!(state.user_win_streaks.is_empty());
+    assert_eq!(state.user_stats.len(), 4);
+}
+
+#[test]
+fn reset_starts_is_matched_as_a_whole_command() {
+    assert!(is_command("/reset_starts tutti", "/reset_starts"));
+    assert!(is_command("/reset_starts@guess_bot 42", "/reset_starts"));
+    assert!(!is_command("/reset_startsfoo tutti", "/reset_starts"));
+}
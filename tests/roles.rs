@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+use telegram_bot_rust::bans::BanList;
+use telegram_bot_rust::privacy::export_user_data;
+use telegram_bot_rust::roles::{Permission, Role, RoleGrants, parse_grantable_role};
+use telegram_bot_rust::{AppState, Config, is_command, stored_permission};
+
+#[test]
+fn roles_are_ordered_by_power() {
+    assert!(Role::Moderator < Role::ChatAdmin);
+    assert!(Role::ChatAdmin < Role::GlobalAdmin);
+    assert!(Role::GlobalAdmin < Role::Owner);
+    // no role at all is below every role
+    assert!(None < Some(Role::Moderator));
+}
+
+#[test]
+fn permissions_require_a_minimum_role() {
+    assert_eq!(Permission::ResetUser.required_role(), Role::Moderator);
+    assert_eq!(Permission::ResetChat.required_role(), Role::ChatAdmin);
+    assert_eq!(Permission::SetChatLang.required_role(), Role::ChatAdmin);
+    assert_eq!(
+        Permission::ManageModerators.required_role(),
+        Role::ChatAdmin
+    );
+    assert_eq!(Permission::ResetStarts.required_role(), Role::GlobalAdmin);
+    assert_eq!(
+        Permission::EraseOthersData.required_role(),
+        Role::GlobalAdmin
+    );
+    assert_eq!(Permission::ReloadMessages.required_role(), Role::Owner);
+    assert_eq!(Permission::ViewAudit.required_role(), Role::Owner);
+    assert_eq!(
+        Permission::to_manage(Role::GlobalAdmin),
+        Permission::ManageGlobalAdmins
+    );
+    assert_eq!(
+        Permission::to_manage(Role::Moderator),
+        Permission::ManageModerators
+    );
+}
+
+#[test]
+fn grants_are_per_chat_for_moderators_and_global_for_admins() {
+    let mut grants = RoleGrants::default();
+    assert!(grants.grant(-100, 7, Role::Moderator));
+    assert!(!grants.grant(-100, 7, Role::Moderator));
+    assert!(grants.grant(-200, 8, Role::GlobalAdmin));
+    // owner and chat admin come from config and Telegram, not from grants
+    assert!(!grants.grant(-100, 9, Role::Owner));
+    assert!(!grants.grant(-100, 9, Role::ChatAdmin));
+
+    assert_eq!(grants.granted_role(-100, 7), Some(Role::Moderator));
+    assert_eq!(grants.granted_role(-300, 7), None);
+    assert_eq!(grants.granted_role(-300, 8), Some(Role::GlobalAdmin));
+    assert_eq!(grants.granted_role(-100, 9), None);
+
+    assert!(grants.revoke(-100, 7, Role::Moderator));
+    assert!(!grants.revoke(-100, 7, Role::Moderator));
+    assert!(grants.moderators.is_empty());
+    assert!(grants.revoke(-200, 8, Role::GlobalAdmin));
+    assert_eq!(grants, RoleGrants::default());
+}
+
+#[test]
+fn grants_round_trip_as_json() {
+    let mut grants = RoleGrants::default();
+    grants.grant(-100, 7, Role::Moderator);
+    grants.grant(-100, 8, Role::GlobalAdmin);
+    let json = serde_json::to_string(&grants).unwrap();
+    let back: RoleGrants = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, grants);
+    let empty: RoleGrants = serde_json::from_str("{}").unwrap();
+    assert_eq!(empty, RoleGrants::default());
+}
+
+#[test]
+fn role_keywords_of_the_command() {
+    assert_eq!(parse_grantable_role("moderatore"), Some(Role::Moderator));
+    assert_eq!(parse_grantable_role("Admin"), Some(Role::GlobalAdmin));
+    assert_eq!(parse_grantable_role("owner"), None);
+    assert_eq!(parse_grantable_role("proprietario"), None);
+}
+
+#[test]
+fn roles_are_part_of_the_data_export() {
+    let mut state = AppState::default();
+    state.role_grants.grant(-100, 7, Role::Moderator);
+    state.role_grants.grant(-200, 7, Role::Moderator);
+    let export = export_user_data(&state, &BanList::default(), 7);
+    assert_eq!(export["roles"]["global_admin"], false);
+    assert_eq!(
+        export["roles"]["moderator_of"],
+        serde_json::json!([-200, -100])
+    );
+}
+
+#[test]
+fn reset_user_starts_pairs_may_only_reset_starts() {
+    let config = Config {
+        bot_owner_id: Some(1),
+        reset_user_starts: HashSet::from(["-100:7".to_string()]),
+        ..Default::default()
+    };
+    let grants = RoleGrants::default();
+    let may = |chat, permission| stored_permission(&config, &grants, chat, 7, permission);
+    assert!(may(-100, Permission::ResetStarts));
+    // only in the listed chat
+    assert!(!may(-200, Permission::ResetStarts));
+    // no global admin powers
+    assert!(!may(-100, Permission::BanGlobally));
+    assert!(!may(-100, Permission::EraseOthersData));
+    assert!(!may(-100, Permission::ManageGlobalAdmins));
+    // the owner keeps everything
+    assert!(stored_permission(
+        &config,
+        &grants,
+        -200,
+        1,
+        Permission::EraseOthersData
+    ));
+}
+
+#[test]
+fn ruoli_is_matched_as_a_whole_command() {
+    assert!(is_command("/ruoli aggiungi 7 admin", "/ruoli"));
+    assert!(!is_command("/ruolixyz", "/ruoli"));
+}
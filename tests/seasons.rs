@@ -0,0 +1,74 @@
+use telegram_bot_rust::seasons::{
+    SeasonPeriod, SeasonState, format_date, next_season_boundary, parse_season_period, win_points,
+};
+use telegram_bot_rust::{default_messages, season_end_announcement, season_report};
+
+// 2025-10-08 12:00:00 UTC, a Wednesday
+const WED_NOON: u64 = 1_759_924_800;
+
+#[test]
+fn parses_season_period_names() {
+    assert_eq!(parse_season_period("weekly"), Some(SeasonPeriod::Weekly));
+    assert_eq!(parse_season_period("Mensile"), Some(SeasonPeriod::Monthly));
+    assert_eq!(parse_season_period("yearly"), None);
+}
+
+#[test]
+fn boundaries_are_next_monday_and_first_of_next_month() {
+    let weekly = next_season_boundary(SeasonPeriod::Weekly, WED_NOON);
+    assert_eq!(format_date(weekly), "2025-10-13");
+    assert_eq!(weekly % 86_400, 0);
+
+    let monthly = next_season_boundary(SeasonPeriod::Monthly, WED_NOON);
+    assert_eq!(format_date(monthly), "2025-11-01");
+
+    // December rolls over into the next year
+    let dec = 1_765_886_400; // 2025-12-16 12:00:00 UTC
+    assert_eq!(
+        format_date(next_season_boundary(SeasonPeriod::Monthly, dec)),
+        "2026-01-01"
+    );
+
+    // a boundary is always strictly in the future, even exactly at midnight
+    assert!(next_season_boundary(SeasonPeriod::Weekly, weekly) > weekly);
+}
+
+#[test]
+fn closing_a_season_archives_champions_and_resets_scores() {
+    let mut season = SeasonState::new(SeasonPeriod::Weekly, WED_NOON);
+    season.record_win(10, 1, "Anna", win_points(5));
+    season.record_win(10, 2, "Luca", win_points(8));
+    season.record_win(10, 1, "Anna", win_points(2));
+    season.record_win(20, 3, "Marco", win_points(0));
+    season.mark_active(30);
+
+    let standings = season.standings(10);
+    assert_eq!(standings[0].0, 1, "Anna has 6 + 3 = 9 points");
+    assert_eq!(standings[0].1.points, 9);
+    assert_eq!(standings[1].1.points, 9, "Luca also has 9 points");
+    assert_eq!(standings[0].1.wins, 2, "ties are broken by wins");
+
+    let end = season.ends_at;
+    assert!(season.is_over(end));
+    let record = season.close(SeasonPeriod::Weekly, end);
+    assert_eq!(record.number, 1);
+    assert_eq!(record.active_chats, vec![10, 20, 30]);
+    assert_eq!(record.champions[&10].name, "Anna");
+    assert_eq!(record.champions[&20].points, 1);
+    assert!(!record.champions.contains_key(&30));
+
+    assert_eq!(season.number, 2);
+    assert!(season.scores.is_empty());
+    assert!(season.active_chats.is_empty());
+    assert!(!season.is_over(end));
+
+    let msgs = default_messages();
+    let announce = season_end_announcement(&record, season.number, 10, &msgs);
+    assert!(announce.contains("Anna"), "announcement was: {}", announce);
+    let nobody = season_end_announcement(&record, season.number, 30, &msgs);
+    assert!(nobody.contains("Nobody"), "announcement was: {}", nobody);
+
+    let report = season_report(&season, &[record], 10, &msgs);
+    assert!(report.contains("Season 2"), "report was: {}", report);
+    assert!(report.contains("Season 1: Anna"), "report was: {}", report);
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use telegram_bot_rust::bans::{Ban, BanList, BanScope};
+use telegram_bot_rust::roles::{Permission, Role};
+use telegram_bot_rust::status::{
+    ConfigSource, ConfigSources, HandlerErrors, RECENT_ERRORS_WINDOW, data_files, format_size,
+    format_uptime, map_sizes,
+};
+use telegram_bot_rust::{AppState, load_all_messages};
+
+#[test]
+fn handler_errors_count_the_last_hour_and_the_total() {
+    let start = Instant::now();
+    let mut errors = HandlerErrors::default();
+    errors.record(start);
+    errors.record(start + Duration::from_secs(60));
+    assert_eq!(errors.recent(start + Duration::from_secs(120)), 2);
+    assert_eq!(errors.recent(start + RECENT_ERRORS_WINDOW), 1);
+    errors.record(start + RECENT_ERRORS_WINDOW * 2);
+    assert_eq!(errors.recent(start + RECENT_ERRORS_WINDOW * 2), 1);
+    assert_eq!(errors.total(), 3);
+}
+
+#[test]
+fn config_sources_tell_env_from_dotenv_and_defaults() {
+    let dotenv = HashMap::from([
+        ("GAME_MIN".to_string(), "1".to_string()),
+        ("GAME_MAX".to_string(), "100".to_string()),
+    ]);
+    let mut sources = ConfigSources::new(dotenv);
+    sources.record("GAME_MIN", Some("1".to_string()));
+    // the process environment overrides .env
+    sources.record("GAME_MAX", Some("50".to_string()));
+    sources.record("GAME_ATTEMPTS", None);
+    sources.record("BOT_OWNER_ID", Some("me".to_string()));
+    sources.invalid("BOT_OWNER_ID");
+    assert_eq!(sources.settings["GAME_MIN"], ConfigSource::DotEnv);
+    assert_eq!(sources.settings["GAME_MAX"], ConfigSource::Environment);
+    assert_eq!(sources.settings["GAME_ATTEMPTS"], ConfigSource::Default);
+    assert_eq!(sources.settings["BOT_OWNER_ID"], ConfigSource::Invalid);
+}
+
+#[test]
+fn map_sizes_count_every_persisted_map() {
+    let mut state = AppState::default();
+    state.seen_welcome.insert("1:7".to_string(), 100);
+    state.seen_welcome.insert("1:8".to_string(), 100);
+    state.role_grants.global_admins.insert(9);
+    state
+        .role_grants
+        .moderators
+        .entry(-100)
+        .or_default()
+        .insert(7);
+    let mut bans = BanList::default();
+    let ban = Ban {
+        by: 9,
+        at: 100,
+        until: None,
+        reason: None,
+    };
+    bans.ban(BanScope::Global, 5, ban.clone());
+    bans.ban(BanScope::Chat(-100), 6, ban);
+    let sizes: HashMap<_, _> = map_sizes(&state, &bans).into_iter().collect();
+    assert_eq!(sizes["seen_welcome"], 2);
+    assert_eq!(sizes["roles"], 2);
+    assert_eq!(sizes["bans"], 2);
+    assert_eq!(sizes["audit_log"], 0);
+}
+
+#[test]
+fn data_files_list_json_files_by_name() {
+    let dir = std::env::temp_dir().join(format!("status_test_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("roles.json"), "{}").unwrap();
+    std::fs::write(dir.join("audit_log.jsonl"), "").unwrap();
+    std::fs::write(dir.join("notes.txt"), "x").unwrap();
+    let files = data_files(&dir);
+    let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["audit_log.jsonl", "roles.json"]);
+    assert_eq!(files[1].size, 2);
+    // written outside of the save helpers: the modification time is shown
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let written = files[1].written.expect("modification time");
+    assert!(now.abs_diff(written) < 60, "{} vs {}", written, now);
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(data_files(&dir).is_empty());
+}
+
+#[test]
+fn uptime_and_sizes_are_compact_and_localized() {
+    let messages = load_all_messages("messages");
+    let en = &messages["en"];
+    assert_eq!(format_uptime(59, en), "0m");
+    assert_eq!(format_uptime(2 * 3600 + 5 * 60, en), "2h 5m");
+    assert_eq!(format_uptime(3 * 86_400 + 60, en), "3d 0h 1m");
+    assert_eq!(format_size(512, en), "512 B");
+    assert_eq!(format_size(1536, en), "1.5 KiB");
+    assert_eq!(format_size(2 * 1024 * 1024, en), "2.0 MiB");
+
+    let it = &messages["it"];
+    assert_eq!(format_uptime(3 * 86_400 + 60, it), "3g 0h 1min");
+    assert_eq!(format_size(1536, it), "1,5 KiB");
+    assert_eq!(format_size(1000 * 1024, it), "1.000,0 KiB");
+
+    // Arabic-Indic digits from `number`, placeholders isolated for bidi
+    let ar = &messages["ar"];
+    let plain = |s: String| s.replace(['\u{2068}', '\u{2069}'], "");
+    assert_eq!(plain(format_uptime(12 * 60, ar)), "١٢ د");
+    assert!(plain(format_size(1536, ar)).starts_with("١٫٥ "));
+}
+
+#[test]
+fn status_is_for_the_owner_and_names_sources() {
+    assert_eq!(Permission::ViewStatus.required_role(), Role::Owner);
+    let messages = load_all_messages("messages");
+    let en = &messages["en"];
+    let line = en.format(
+        &en.status_config_line,
+        &[("name", "GAME_MIN"), ("source", "dotenv")],
+    );
+    assert_eq!(line, "• GAME_MIN: .env file");
+    let line = en.format(
+        &en.status_file_line,
+        &[
+            ("file", "roles.json"),
+            ("size", "2 B"),
+            ("written", &en.status_not_written),
+        ],
+    );
+    assert_eq!(line, "• roles.json: 2 B, last write unknown");
+    let written = en.format(&en.status_written_at, &[("time", "2026-10-18 10:00")]);
+    let line = en.format(
+        &en.status_file_line,
+        &[
+            ("file", "roles.json"),
+            ("size", "2 B"),
+            ("written", &written),
+        ],
+    );
+    assert_eq!(line, "• roles.json: 2 B, last written 2026-10-18 10:00 UTC");
+}
@@ -0,0 +1,65 @@
+use std::collections::{BTreeSet, HashMap};
+use telegram_bot_rust::validation::{IssueKind, template_placeholders, validate_placeholders};
+use telegram_bot_rust::{default_messages, load_all_messages};
+
+fn set(names: &[&str]) -> Option<BTreeSet<String>> {
+    Some(names.iter().map(|n| n.to_string()).collect())
+}
+
+#[test]
+fn placeholders_include_plural_keys_and_arms() {
+    assert_eq!(
+        template_placeholders("Between {min} and {max}: {attempts}"),
+        set(&["attempts", "max", "min"])
+    );
+    assert_eq!(
+        template_placeholders("{n, plural, one {# in {box}} other {# items}}"),
+        set(&["box", "n"])
+    );
+    assert_eq!(template_placeholders("no placeholders"), set(&[]));
+    assert_eq!(template_placeholders("open {attempts"), None);
+    assert_eq!(template_placeholders("close attempts}"), None);
+    assert_eq!(template_placeholders("{a}} {b}"), None);
+}
+
+#[test]
+fn translator_mistakes_are_reported() {
+    let mut broken = default_messages();
+    broken.too_low = "Troppo basso.".to_string();
+    broken.success_correct = broken
+        .success_correct
+        .replace("{next_attempts,", "{next_atempts,");
+    broken.pong = "pong {".to_string();
+    let mut map = HashMap::new();
+    map.insert("it".to_string(), broken);
+    map.insert("en".to_string(), default_messages());
+
+    let issues = validate_placeholders(&map);
+    let found: Vec<(String, String, IssueKind)> = issues
+        .into_iter()
+        .map(|i| (i.lang, i.key, i.kind))
+        .collect();
+    let it = |key: &str, kind: IssueKind| ("it".to_string(), key.to_string(), kind);
+    assert_eq!(
+        found,
+        vec![
+            it("pong", IssueKind::UnbalancedBraces),
+            it(
+                "success_correct",
+                IssueKind::Missing("next_attempts".to_string())
+            ),
+            it(
+                "success_correct",
+                IssueKind::Unknown("next_atempts".to_string())
+            ),
+            it("too_low", IssueKind::Missing("attempts".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn shipped_translations_have_valid_placeholders() {
+    let issues = validate_placeholders(&load_all_messages("messages"));
+    let lines: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+    assert!(lines.is_empty(), "{}", lines.join("\n"));
+}